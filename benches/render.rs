@@ -0,0 +1,146 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use os_terminal::font::{ContentInfo, FontManager, Rasterized};
+use os_terminal::{DrawTarget, Rgb, Terminal};
+
+const COLUMNS: usize = 80;
+const ROWS: usize = 24;
+const FONT_SIZE: (usize, usize) = (8, 16);
+
+struct DummyDisplay {
+    width: usize,
+    height: usize,
+    buffer: Vec<Rgb>,
+}
+
+impl DummyDisplay {
+    fn new(width: usize, height: usize) -> Self {
+        let buffer = vec![(0, 0, 0); width * height];
+        Self {
+            width,
+            height,
+            buffer,
+        }
+    }
+}
+
+impl DrawTarget for DummyDisplay {
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn draw_pixel(&mut self, x: usize, y: usize, color: Rgb) {
+        self.buffer[y * self.width + x] = color;
+    }
+}
+
+struct DummyFont {
+    bitmap: Vec<Vec<u8>>,
+}
+
+impl DummyFont {
+    fn new() -> Self {
+        let (width, height) = FONT_SIZE;
+        let bitmap = (0..height)
+            .map(|y| (0..width).map(|x| ((x + y) % 2 * 0xff) as u8).collect())
+            .collect();
+        Self { bitmap }
+    }
+}
+
+impl FontManager for DummyFont {
+    fn size(&self) -> (usize, usize) {
+        FONT_SIZE
+    }
+
+    fn rasterize(&mut self, _info: ContentInfo) -> Rasterized<'_> {
+        Rasterized::Vec(&self.bitmap)
+    }
+}
+
+fn new_terminal() -> Terminal<DummyDisplay> {
+    let (width, height) = (COLUMNS * FONT_SIZE.0, ROWS * FONT_SIZE.1);
+    let mut terminal = Terminal::new(DummyDisplay::new(width, height));
+    terminal.set_font_manager(Box::new(DummyFont::new()));
+    terminal.set_auto_flush(false);
+    terminal.flush();
+    terminal
+}
+
+fn fill_screen(terminal: &mut Terminal<DummyDisplay>, content: u8) {
+    let line = vec![content; COLUMNS];
+    for row in 1..=ROWS {
+        terminal.process(format!("\x1b[{};1H", row).as_bytes());
+        terminal.process(&line);
+    }
+}
+
+fn bench_flush(c: &mut Criterion) {
+    let mut terminal = new_terminal();
+    let mut iteration = 0u8;
+    c.bench_function("flush 80x24, 100% changed", |b| {
+        b.iter(|| {
+            iteration = iteration.wrapping_add(1);
+            fill_screen(&mut terminal, b'a' + iteration % 2);
+            terminal.flush();
+        })
+    });
+
+    let mut terminal = new_terminal();
+    fill_screen(&mut terminal, b'a');
+    terminal.flush();
+    let mut iteration = 0usize;
+    c.bench_function("flush 80x24, 10% changed", |b| {
+        b.iter(|| {
+            iteration = iteration.wrapping_add(1);
+            let content = [b'b' + (iteration % 2) as u8; COLUMNS / 10];
+            let column = (iteration % 10) * COLUMNS / 10 + 1;
+            for row in 1..=ROWS {
+                terminal.process(format!("\x1b[{};{}H", row, column).as_bytes());
+                terminal.process(&content);
+            }
+            terminal.flush();
+        })
+    });
+
+    let mut terminal = new_terminal();
+    fill_screen(&mut terminal, b'a');
+    terminal.flush();
+    c.bench_function("flush 80x24, 0% changed", |b| b.iter(|| terminal.flush()));
+}
+
+#[cfg(feature = "truetype")]
+fn bench_rasterize(c: &mut Criterion) {
+    use os_terminal::font::TrueTypeFont;
+    use std::hint::black_box;
+
+    let font_buffer = include_bytes!("../examples/FiraCodeNotoSans.ttf");
+
+    let mut font = TrueTypeFont::new(10.0, font_buffer);
+    font.rasterize(ContentInfo::new('a', false, false, false));
+    c.bench_function("rasterize, warm cache", |b| {
+        b.iter(|| {
+            black_box(font.rasterize(ContentInfo::new('a', false, false, false)));
+        })
+    });
+
+    let mut font = TrueTypeFont::new(10.0, font_buffer).with_cache_size(1);
+    let mut iteration = 0usize;
+    c.bench_function("rasterize, cold cache", |b| {
+        b.iter(|| {
+            iteration = iteration.wrapping_add(1);
+            let content = if iteration.is_multiple_of(2) {
+                'a'
+            } else {
+                'b'
+            };
+            black_box(font.rasterize(ContentInfo::new(content, false, false, false)));
+        })
+    });
+}
+
+#[cfg(feature = "truetype")]
+criterion_group!(benches, bench_flush, bench_rasterize);
+#[cfg(not(feature = "truetype"))]
+criterion_group!(benches, bench_flush);
+
+criterion_main!(benches);
@@ -0,0 +1,89 @@
+// Built as a library so it compiles on the host. In a kernel the same code
+// lives next to your `#[panic_handler]` and `#[global_allocator]`.
+#![no_std]
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use core::fmt::{self, Write};
+use spin::Mutex;
+
+use os_terminal::draw::{rgb_to_pixel, DrawTarget};
+use os_terminal::font::BitmapFont;
+use os_terminal::{Rgb, Terminal};
+
+pub struct Framebuffer {
+    address: *mut u32,
+    width: usize,
+    height: usize,
+    stride: usize,
+}
+
+// The framebuffer is only reached through the terminal lock below.
+unsafe impl Send for Framebuffer {}
+
+impl DrawTarget for Framebuffer {
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    #[inline(always)]
+    fn draw_pixel(&mut self, x: usize, y: usize, color: Rgb) {
+        let pixel = rgb_to_pixel(color);
+        unsafe { self.address.add(y * self.stride + x).write_volatile(pixel) }
+    }
+}
+
+static TERMINAL: Mutex<Option<Terminal<Framebuffer>>> = Mutex::new(None);
+
+/// # Safety
+///
+/// `address` must point to a linear 32-bit framebuffer of `stride * height`
+/// pixels that stays mapped for the lifetime of the kernel.
+pub unsafe fn init(address: *mut u32, width: usize, height: usize, stride: usize) {
+    let framebuffer = Framebuffer {
+        address,
+        width,
+        height,
+        stride,
+    };
+
+    let mut terminal = Terminal::new(framebuffer);
+    terminal.set_font_manager(Box::new(BitmapFont));
+    terminal.set_auto_crnl(true);
+    *TERMINAL.lock() = Some(terminal);
+}
+
+pub fn _print(args: fmt::Arguments) {
+    if let Some(terminal) = TERMINAL.lock().as_mut() {
+        terminal.write_fmt(args).unwrap();
+    }
+}
+
+#[macro_export]
+macro_rules! print {
+    ($($arg:tt)*) => ($crate::_print(format_args!($($arg)*)));
+}
+
+#[macro_export]
+macro_rules! println {
+    () => ($crate::print!("\n"));
+    ($($arg:tt)*) => ($crate::print!("{}\n", format_args!($($arg)*)));
+}
+
+pub fn handle_scancode(scancode: u8) {
+    let mut terminal = TERMINAL.lock();
+    let Some(terminal) = terminal.as_mut() else {
+        return;
+    };
+
+    // A real kernel forwards this to the tty of the foreground process.
+    if let Some(input) = terminal.handle_keyboard(scancode) {
+        terminal.process(input.as_bytes());
+    }
+}
+
+pub fn kernel_main() {
+    println!("\x1b[32mHello from a no_std kernel!\x1b[0m");
+    println!("{} + {} = {}", 1, 2, 1 + 2);
+}
@@ -14,13 +14,15 @@ use nix::errno::Errno;
 use nix::libc::{ioctl, TIOCSCTTY, TIOCSWINSZ};
 use nix::pty::{openpty, OpenptyResult, Winsize};
 use nix::unistd::{close, dup2, execvp, fork, read, setsid, write, ForkResult};
+use os_terminal::draw::{rgb_to_pixel, DrawTarget};
 use os_terminal::font::TrueTypeFont;
-use os_terminal::{DrawTarget, Rgb, Terminal};
+use os_terminal::{MouseButton, MouseInput, Rgb, Terminal};
 
 use softbuffer::{Context, Surface};
 use winit::application::ApplicationHandler;
 use winit::dpi::PhysicalSize;
-use winit::event::{ElementState, Ime, WindowEvent};
+use winit::event::MouseButton as WinitMouseButton;
+use winit::event::{ElementState, Ime, MouseScrollDelta, WindowEvent};
 use winit::event_loop::{ActiveEventLoop, EventLoop, EventLoopProxy};
 use winit::platform::scancode::PhysicalKeyExtScancode;
 use winit::window::{ImePurpose, Window, WindowAttributes, WindowId};
@@ -79,6 +81,14 @@ fn main() {
             let redraw_event_proxy = event_loop.create_proxy();
             let (ansi_sender, ansi_receiver) = unbounded();
 
+            let pty_sender = ansi_sender.clone();
+            terminal
+                .lock()
+                .unwrap()
+                .set_pty_writer(Box::new(move |data: &str| {
+                    pty_sender.send(data.into()).unwrap();
+                }));
+
             let mut app = App::new(
                 ansi_sender,
                 buffer.clone(),
@@ -145,7 +155,7 @@ impl DrawTarget for Display {
 
     #[inline(always)]
     fn draw_pixel(&mut self, x: usize, y: usize, color: Rgb) {
-        let value = (color.0 as u32) << 16 | (color.1 as u32) << 8 | color.2 as u32;
+        let value = rgb_to_pixel(color);
         self.buffer[y * self.width + x].store(value, Ordering::Relaxed);
     }
 }
@@ -216,62 +226,82 @@ impl ApplicationHandler for App {
         event: WindowEvent,
     ) {
         let window = self.window.as_ref().unwrap();
+        if window_id != window.id() {
+            return;
+        }
 
         match event {
             WindowEvent::RedrawRequested => {
-                if window_id == window.id() {
-                    let surface = self.surface.as_mut().unwrap();
-                    self.terminal.lock().unwrap().flush();
+                let surface = self.surface.as_mut().unwrap();
+                self.terminal.lock().unwrap().flush();
 
-                    let mut surface_buffer = surface.buffer_mut().unwrap();
-                    for (index, value) in self.buffer.iter().enumerate() {
-                        surface_buffer[index] = value.load(Ordering::Relaxed);
-                    }
-                    surface_buffer.present().unwrap();
+                let mut surface_buffer = surface.buffer_mut().unwrap();
+                for (index, value) in self.buffer.iter().enumerate() {
+                    surface_buffer[index] = value.load(Ordering::Relaxed);
                 }
+                surface_buffer.present().unwrap();
             }
-            WindowEvent::CloseRequested => {
-                if window_id == window.id() {
-                    event_loop.exit();
-                }
+            WindowEvent::CloseRequested => event_loop.exit(),
+            WindowEvent::Ime(Ime::Preedit(text, cursor)) => {
+                let mut terminal = self.terminal.lock().unwrap();
+                terminal.handle_ime_preedit(&text, cursor);
+                self.redraw_event_proxy.send_event(()).unwrap();
             }
-            WindowEvent::Ime(ime) => {
-                if window_id == window.id() {
-                    match ime {
-                        Ime::Commit(text) => {
-                            self.ansi_sender.send(text).unwrap();
-                            self.redraw_event_proxy.send_event(()).unwrap();
-                        }
-                        _ => {}
-                    }
-                }
+            WindowEvent::Ime(Ime::Commit(text)) => {
+                self.ansi_sender.send(text).unwrap();
+                self.redraw_event_proxy.send_event(()).unwrap();
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let input = MouseInput::Moved(position.x as usize, position.y as usize);
+                self.terminal.lock().unwrap().handle_mouse(input);
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                let button = match button {
+                    WinitMouseButton::Left => MouseButton::Left,
+                    WinitMouseButton::Middle => MouseButton::Middle,
+                    WinitMouseButton::Right => MouseButton::Right,
+                    _ => return,
+                };
+                let input = match state {
+                    ElementState::Pressed => MouseInput::Pressed(button),
+                    ElementState::Released => MouseInput::Released(button),
+                };
+                self.terminal.lock().unwrap().handle_mouse(input);
+                self.redraw_event_proxy.send_event(()).unwrap();
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                let lines = match delta {
+                    MouseScrollDelta::LineDelta(_, lines) => lines as isize,
+                    MouseScrollDelta::PixelDelta(delta) => (delta.y / 16.0) as isize,
+                };
+                let mut terminal = self.terminal.lock().unwrap();
+                terminal.handle_mouse(MouseInput::Scroll(lines));
+                self.redraw_event_proxy.send_event(()).unwrap();
             }
             WindowEvent::KeyboardInput { event, .. } => {
-                if window_id == window.id() {
-                    if let Some(evdev_code) = event.physical_key.to_scancode() {
-                        if let Ok(keymap) =
-                            KeyMap::from_key_mapping(KeyMapping::Evdev(evdev_code as u16))
+                if let Some(evdev_code) = event.physical_key.to_scancode() {
+                    if let Ok(keymap) =
+                        KeyMap::from_key_mapping(KeyMapping::Evdev(evdev_code as u16))
+                    {
+                        // Windows scancode is 16-bit extended scancode
+                        let mut scancode = keymap.win;
+                        if event.state == ElementState::Released {
+                            scancode += 0x80;
+                        }
+                        if scancode >= 0xe000 {
+                            self.terminal.lock().unwrap().handle_keyboard(0xe0);
+                            scancode -= 0xe000;
+                        }
+                        if let Some(ansi_string) = self
+                            .terminal
+                            .lock()
+                            .unwrap()
+                            .handle_keyboard(scancode as u8)
                         {
-                            // Windows scancode is 16-bit extended scancode
-                            let mut scancode = keymap.win;
-                            if event.state == ElementState::Released {
-                                scancode += 0x80;
-                            }
-                            if scancode >= 0xe000 {
-                                self.terminal.lock().unwrap().handle_keyboard(0xe0);
-                                scancode -= 0xe000;
-                            }
-                            if let Some(ansi_string) = self
-                                .terminal
-                                .lock()
-                                .unwrap()
-                                .handle_keyboard(scancode as u8)
-                            {
-                                self.ansi_sender.send(ansi_string).unwrap();
-                            }
-
-                            self.redraw_event_proxy.send_event(()).unwrap();
+                            self.ansi_sender.send(ansi_string).unwrap();
                         }
+
+                        self.redraw_event_proxy.send_event(()).unwrap();
                     }
                 }
             }
@@ -0,0 +1,68 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::cell::{Cell, Flags};
+use crate::color::Rgb;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessibleSpan {
+    pub text: String,
+    pub foreground: Rgb,
+    pub bold: bool,
+    pub italic: bool,
+    pub blink: bool,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AccessibleLine {
+    pub spans: Vec<AccessibleSpan>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AccessibleContent {
+    pub lines: Vec<AccessibleLine>,
+}
+
+impl AccessibleSpan {
+    fn new(cell: &Cell) -> Self {
+        Self {
+            text: String::new(),
+            foreground: cell.foreground.to_rgb(),
+            bold: cell.flags.contains(Flags::BOLD),
+            italic: cell.flags.contains(Flags::ITALIC),
+            blink: cell.flags.contains(Flags::BLINK),
+        }
+    }
+
+    fn same_style(&self, other: &Self) -> bool {
+        self.foreground == other.foreground
+            && self.bold == other.bold
+            && self.italic == other.italic
+            && self.blink == other.blink
+    }
+}
+
+impl AccessibleLine {
+    pub fn from_cells(cells: &[Cell]) -> Self {
+        let mut spans: Vec<AccessibleSpan> = Vec::new();
+
+        for cell in cells.iter().filter(|cell| !cell.placeholder) {
+            let content = if cell.flags.contains(Flags::HIDDEN) {
+                ' '
+            } else {
+                cell.content
+            };
+
+            let mut span = AccessibleSpan::new(cell);
+            match spans.last_mut() {
+                Some(last) if last.same_style(&span) => last.text.push(content),
+                _ => {
+                    span.text.push(content);
+                    spans.push(span);
+                }
+            }
+        }
+
+        Self { spans }
+    }
+}
@@ -1,12 +1,26 @@
 use alloc::collections::vec_deque::VecDeque;
+use alloc::string::String;
 use alloc::vec::Vec;
+use core::cmp::min;
 use core::mem::swap;
+use core::time::Duration;
 
-use crate::cell::Cell;
-use crate::graphic::{DrawTarget, Graphic};
+use crate::cell::{Cell, Flags, LineAttr};
+use crate::color::Rgb;
+use crate::config::CONFIG;
+use crate::draw::DrawTarget;
+use crate::graphic::{Graphic, RenderStats};
 
 const INIT_SIZE: (usize, usize) = (1, 1);
 const DEFAULT_HISTORY_SIZE: usize = 200;
+const DEFAULT_SCROLLBAR_COLORS: (Rgb, Rgb) = ((0x20, 0x20, 0x20), (0x80, 0x80, 0x80));
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryStrategy {
+    #[default]
+    OldestFirst,
+    FurthestFromCursor,
+}
 
 pub struct FixedStack<T> {
     data: VecDeque<T>,
@@ -43,6 +57,11 @@ impl<T> FixedStack<T> {
         self.data.pop_back()
     }
 
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.data.pop_front()
+    }
+
     #[inline]
     pub fn clear(&mut self) {
         self.data.clear();
@@ -65,8 +84,18 @@ pub struct TerminalBuffer<D: DrawTarget> {
     flush_cache: VecDeque<Vec<Cell>>,
     buffer: VecDeque<Vec<Cell>>,
     alt_buffer: VecDeque<Vec<Cell>>,
+    line_attrs: VecDeque<LineAttr>,
+    alt_line_attrs: VecDeque<LineAttr>,
+    flush_line_attrs: VecDeque<LineAttr>,
     above_buffer: FixedStack<Vec<Cell>>,
     below_buffer: FixedStack<Vec<Cell>>,
+    history_size: usize,
+    history_strategy: HistoryStrategy,
+    scrollbar_width: usize,
+    scrollbar_colors: (Rgb, Rgb),
+    scrollbar_cache: Option<(usize, usize)>,
+    full_redraw: bool,
+    partial_flush: Option<Option<Duration>>,
 }
 
 impl<D: DrawTarget> TerminalBuffer<D> {
@@ -79,6 +108,11 @@ impl<D: DrawTarget> TerminalBuffer<D> {
     pub fn height(&self) -> usize {
         self.size.1
     }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.width() == 0 || self.height() == 0
+    }
 }
 
 impl<D: DrawTarget> TerminalBuffer<D> {
@@ -93,36 +127,93 @@ impl<D: DrawTarget> TerminalBuffer<D> {
             buffer: buffer.clone().into(),
             alt_buffer: buffer.clone().into(),
             flush_cache: buffer.into(),
+            line_attrs: vec![LineAttr::Normal; INIT_SIZE.1].into(),
+            alt_line_attrs: vec![LineAttr::Normal; INIT_SIZE.1].into(),
+            flush_line_attrs: vec![LineAttr::Normal; INIT_SIZE.1].into(),
             above_buffer: FixedStack::new(DEFAULT_HISTORY_SIZE),
             below_buffer: FixedStack::new(DEFAULT_HISTORY_SIZE),
+            history_size: DEFAULT_HISTORY_SIZE,
+            history_strategy: HistoryStrategy::default(),
+            scrollbar_width: 0,
+            scrollbar_colors: DEFAULT_SCROLLBAR_COLORS,
+            scrollbar_cache: None,
+            full_redraw: false,
+            partial_flush: None,
         }
     }
 
     pub fn swap_alt_screen(&mut self, cell: Cell) {
         self.alt_screen_mode = !self.alt_screen_mode;
         swap(&mut self.buffer, &mut self.alt_buffer);
+        swap(&mut self.line_attrs, &mut self.alt_line_attrs);
 
         if self.alt_screen_mode {
             self.clear(cell);
         }
     }
 
-    pub fn update_size(&mut self, font_width: usize, font_height: usize) {
-        if font_width == 0 || font_height == 0 {
-            return;
-        }
-
-        let width = self.graphic.width() / font_width;
-        let height = self.graphic.height() / font_height;
+    pub fn update_size(
+        &mut self,
+        font_width: usize,
+        font_height: usize,
+        cursor_row: usize,
+    ) -> usize {
+        self.graphic.update_size();
+        let (width, height) = if font_width == 0 || font_height == 0 {
+            (0, 0)
+        } else {
+            let width = self.graphic.width().saturating_sub(self.scrollbar_width) / font_width;
+            (width, self.graphic.height() / font_height)
+        };
         self.pixel_size = (font_width * width, font_height * height);
 
-        if self.size != (width, height) {
-            let buffer = vec![vec![Cell::default(); width]; height].into();
-            self.size = (width, height);
-            self.buffer.clone_from(&buffer);
-            self.alt_buffer.clone_from(&buffer);
-            self.flush_cache = buffer;
+        if self.size == (width, height) {
+            return 0;
+        }
+
+        let removed = (cursor_row + 1).saturating_sub(height).min(self.height());
+        let mut history = Vec::new();
+        let screens = [
+            (
+                &mut self.buffer,
+                &mut self.line_attrs,
+                !self.alt_screen_mode,
+            ),
+            (
+                &mut self.alt_buffer,
+                &mut self.alt_line_attrs,
+                self.alt_screen_mode,
+            ),
+        ];
+        for (buffer, line_attrs, is_main) in screens {
+            for row in buffer.drain(..removed) {
+                if is_main {
+                    history.push(row);
+                }
+            }
+            line_attrs.drain(..removed);
+            buffer.resize(height, Vec::new());
+            buffer
+                .iter_mut()
+                .for_each(|row| row.resize(width, Cell::default()));
+            line_attrs.resize(height, LineAttr::Normal);
         }
+        history
+            .into_iter()
+            .for_each(|row| self.push_history(row, true));
+
+        self.size = (width, height);
+        self.flush_cache = vec![Vec::new(); height].into();
+        self.flush_line_attrs = vec![LineAttr::Normal; height].into();
+        self.full_redraw = true;
+
+        self.above_buffer
+            .data
+            .iter_mut()
+            .chain(self.below_buffer.data.iter_mut())
+            .for_each(|row| row.resize(width, Cell::default()));
+
+        removed
     }
 }
 
@@ -139,28 +230,159 @@ impl<D: DrawTarget> TerminalBuffer<D> {
         self.buffer[row][col] = cell;
     }
 
+    #[inline]
+    pub fn rows(&self) -> impl Iterator<Item = &[Cell]> {
+        self.buffer.iter().map(|row| row.as_slice())
+    }
+
     #[inline]
     pub fn clear(&mut self, cell: Cell) {
         self.buffer
             .iter_mut()
             .flat_map(|row| row.iter_mut())
             .for_each(|c| *c = cell);
+        self.line_attrs
+            .iter_mut()
+            .for_each(|attr| *attr = LineAttr::Normal);
+    }
+
+    #[inline]
+    pub fn line_attr(&self, row: usize) -> LineAttr {
+        self.line_attrs[row % self.height()]
+    }
+
+    #[inline]
+    pub fn set_line_attr(&mut self, row: usize, attr: LineAttr) {
+        let row = row % self.height();
+        self.line_attrs[row] = attr;
     }
 }
 
 impl<D: DrawTarget> TerminalBuffer<D> {
+    pub fn detect_urls(&mut self) {
+        for (row, cache) in self.buffer.iter_mut().zip(self.flush_cache.iter()) {
+            if row != cache {
+                mark_urls(row);
+            }
+        }
+    }
+
+    pub fn url_at(&self, row: usize, col: usize) -> Option<String> {
+        let row = &self.buffer[row % self.height()];
+        if !row[col].flags.contains(Flags::URL) {
+            return None;
+        }
+
+        let is_url = |cell: &Cell| cell.flags.contains(Flags::URL);
+        let start = row[..col]
+            .iter()
+            .rposition(|c| !is_url(c))
+            .map_or(0, |i| i + 1);
+        let end = row[col..]
+            .iter()
+            .position(|c| !is_url(c))
+            .map_or(row.len(), |i| col + i);
+
+        let url = row[start..end].iter().filter(|c| !c.placeholder);
+        Some(url.map(|c| c.content).collect())
+    }
+
+    pub fn draw_target(&mut self) -> &mut D {
+        self.graphic.draw_target()
+    }
+
     pub fn flush(&mut self) {
-        for (i, row) in self.buffer.iter().enumerate() {
-            for (j, &cell) in row.iter().enumerate() {
-                if cell != self.flush_cache[i][j] {
-                    self.graphic.write(i, j, cell);
-                    self.flush_cache[i][j] = cell;
-                }
+        let start_time = match self.partial_flush.take() {
+            Some(start_time) => start_time,
+            None => self.graphic.begin_flush(),
+        };
+
+        for row in 0..self.buffer.len() {
+            self.draw_row(row);
+        }
+
+        self.finish_flush(start_time);
+    }
+
+    pub fn draw_partial_row(&mut self, row: usize) {
+        if self.partial_flush.is_none() {
+            self.partial_flush = Some(self.graphic.begin_flush());
+        }
+        self.draw_row(row);
+    }
+
+    pub fn finish_partial_flush(&mut self) {
+        let start_time = match self.partial_flush.take() {
+            Some(start_time) => start_time,
+            None => self.graphic.begin_flush(),
+        };
+        self.finish_flush(start_time);
+    }
+
+    fn finish_flush(&mut self, start_time: Option<Duration>) {
+        if self.full_redraw {
+            self.full_redraw = false;
+            self.graphic.clear(
+                (0, self.pixel_size.1),
+                (self.pixel_size.0, self.graphic.height()),
+                Cell::default(),
+            );
+            self.graphic.clear(
+                (self.pixel_size.0, 0),
+                (self.graphic.width(), self.graphic.height()),
+                Cell::default(),
+            );
+            self.scrollbar_cache = None;
+        }
+
+        self.draw_scrollbar();
+        self.graphic.end_flush(start_time);
+    }
+
+    pub fn dirty_rows(&self) -> impl Iterator<Item = usize> + '_ {
+        self.buffer
+            .iter()
+            .zip(self.flush_cache.iter())
+            .enumerate()
+            .filter(|(i, (row, cache))| {
+                row != cache || self.line_attrs[*i] != self.flush_line_attrs[*i]
+            })
+            .map(|(i, _)| i)
+    }
+
+    fn draw_row(&mut self, row: usize) {
+        if row >= self.buffer.len() {
+            return;
+        }
+        let line_attr = self.line_attrs[row];
+        let columns = match line_attr {
+            LineAttr::Normal => self.width(),
+            _ => self.width() / 2,
+        };
+
+        if self.flush_cache[row].len() != self.buffer[row].len()
+            || self.flush_line_attrs[row] != line_attr
+        {
+            for (j, &cell) in self.buffer[row][..columns].iter().enumerate() {
+                self.graphic.write(row, j, cell, line_attr);
+            }
+            self.flush_cache[row].clone_from(&self.buffer[row]);
+            self.flush_line_attrs[row] = line_attr;
+            return;
+        }
+        for (j, &cell) in self.buffer[row][..columns].iter().enumerate() {
+            if cell != self.flush_cache[row][j] {
+                self.graphic.write(row, j, cell, line_attr);
+                self.flush_cache[row][j] = cell;
             }
         }
     }
 
-    pub fn full_flush(&mut self) {
+    pub fn invalidate(&mut self) {
+        self.flush_cache.iter_mut().for_each(Vec::clear);
+    }
+
+    pub fn reset_colors(&mut self) {
         macro_rules! reset_buffer {
             ($buffer:expr) => {
                 $buffer
@@ -175,22 +397,67 @@ impl<D: DrawTarget> TerminalBuffer<D> {
         reset_buffer!(self.above_buffer.data);
         reset_buffer!(self.below_buffer.data);
 
-        for (i, row) in self.buffer.iter().enumerate() {
-            for (j, &cell) in row.iter().enumerate() {
-                self.graphic.write(i, j, cell);
-            }
+        self.invalidate();
+        self.full_redraw = true;
+    }
+
+    pub fn set_scrollbar_width(&mut self, width: usize) {
+        self.scrollbar_width = width;
+        self.scrollbar_cache = None;
+    }
+
+    pub fn set_scrollbar_colors(&mut self, track: Rgb, thumb: Rgb) {
+        self.scrollbar_colors = (track, thumb);
+        self.scrollbar_cache = None;
+    }
+
+    fn draw_scrollbar(&mut self) {
+        if self.scrollbar_width == 0 {
+            return;
         }
 
-        self.graphic.clear(
-            (0, self.pixel_size.1),
-            (self.pixel_size.0, self.graphic.height()),
-            Cell::default(),
-        );
-        self.graphic.clear(
-            (self.pixel_size.0, 0),
-            (self.graphic.width(), self.graphic.height()),
-            Cell::default(),
-        );
+        let (width, height) = self.graphic.size();
+        let (above, below) = if self.alt_screen_mode {
+            (0, 0)
+        } else {
+            (self.above_buffer.len(), self.below_buffer.len())
+        };
+
+        let total = above + self.height() + below;
+        if total == 0 {
+            return;
+        }
+        let thumb_top = height * above / total;
+        let thumb_height = (height * self.height() / total).max(1);
+
+        if self.scrollbar_cache == Some((thumb_top, thumb_height)) {
+            return;
+        }
+        self.scrollbar_cache = Some((thumb_top, thumb_height));
+
+        let (track, thumb) = self.scrollbar_colors;
+        let x_start = width.saturating_sub(self.scrollbar_width);
+        let thumb_end = min(thumb_top + thumb_height, height);
+
+        self.graphic.fill((x_start, 0), (width, thumb_top), track);
+        self.graphic
+            .fill((x_start, thumb_top), (width, thumb_end), thumb);
+        self.graphic
+            .fill((x_start, thumb_end), (width, height), track);
+    }
+
+    #[inline]
+    pub fn render_stats(&self) -> RenderStats {
+        self.graphic.stats()
+    }
+
+    #[inline]
+    pub fn reset_stats(&mut self) {
+        self.graphic.reset_stats();
+    }
+
+    pub fn set_color_cache_size(&mut self, size: usize) {
+        self.graphic.set_color_cache_size(size);
     }
 }
 
@@ -211,6 +478,15 @@ impl<D: DrawTarget> TerminalBuffer<D> {
         }
     }
 
+    #[inline]
+    pub fn history_offset(&self) -> usize {
+        if self.alt_screen_mode {
+            0
+        } else {
+            self.below_buffer.len()
+        }
+    }
+
     #[inline]
     pub fn clear_history(&mut self) {
         if !self.alt_screen_mode {
@@ -223,6 +499,37 @@ impl<D: DrawTarget> TerminalBuffer<D> {
     pub fn resize_history(&mut self, new_capacity: usize) {
         self.above_buffer.resize(new_capacity);
         self.below_buffer.resize(new_capacity);
+        self.history_size = new_capacity;
+        self.trim_history();
+    }
+
+    pub fn set_history_strategy(&mut self, strategy: HistoryStrategy) {
+        self.history_strategy = strategy;
+    }
+
+    fn push_history(&mut self, row: Vec<Cell>, is_above: bool) {
+        if is_above {
+            self.above_buffer.push(row);
+        } else {
+            self.below_buffer.push(row);
+        }
+        self.trim_history();
+    }
+
+    fn trim_history(&mut self) {
+        while self.above_buffer.len() + self.below_buffer.len() > self.history_size {
+            let trim_above = match self.history_strategy {
+                HistoryStrategy::OldestFirst => !self.above_buffer.is_empty(),
+                HistoryStrategy::FurthestFromCursor => {
+                    self.above_buffer.len() >= self.below_buffer.len()
+                }
+            };
+            if trim_above {
+                self.above_buffer.pop_front();
+            } else {
+                self.below_buffer.pop_front();
+            }
+        }
     }
 }
 
@@ -234,29 +541,38 @@ impl<D: DrawTarget> TerminalBuffer<D> {
         is_up: bool,
         scrolling_region: (usize, usize),
     ) {
+        if count == 0 {
+            return;
+        }
+
         let (top, bottom) = scrolling_region;
         let new_row = vec![cell; self.width()];
+        let full_screen = top == 0 && bottom == self.height() - 1;
 
         for _ in 0..count {
             if is_up {
                 let row = self.buffer.remove(top).unwrap();
-                if !self.alt_screen_mode && bottom == self.height() - 1 {
-                    self.above_buffer.push(row);
+                if !self.alt_screen_mode && full_screen {
+                    self.push_history(row, true);
                 }
                 self.buffer.insert(bottom, new_row.clone());
+                self.line_attrs.remove(top);
+                self.line_attrs.insert(bottom, LineAttr::Normal);
             } else {
                 let row = self.buffer.remove(bottom).unwrap();
-                if !self.alt_screen_mode && top == 0 {
-                    self.below_buffer.push(row);
+                if !self.alt_screen_mode && full_screen {
+                    self.push_history(row, false);
                 }
                 self.buffer.insert(top, new_row.clone());
+                self.line_attrs.remove(bottom);
+                self.line_attrs.insert(top, LineAttr::Normal);
             }
         }
     }
 
-    pub fn scroll_history(&mut self, count: usize, is_up: bool) {
-        if self.alt_screen_mode {
-            return;
+    pub fn scroll_history(&mut self, count: usize, is_up: bool) -> usize {
+        if self.alt_screen_mode || self.is_empty() {
+            return 0;
         }
 
         let moves = if is_up {
@@ -270,11 +586,50 @@ impl<D: DrawTarget> TerminalBuffer<D> {
                 let row = self.buffer.pop_front().unwrap();
                 self.above_buffer.push(row);
                 self.buffer.push_back(self.below_buffer.pop().unwrap());
+                self.line_attrs.pop_front();
+                self.line_attrs.push_back(LineAttr::Normal);
             } else {
                 let row = self.buffer.pop_back().unwrap();
                 self.below_buffer.push(row);
                 self.buffer.push_front(self.above_buffer.pop().unwrap());
+                self.line_attrs.pop_back();
+                self.line_attrs.push_front(LineAttr::Normal);
+            }
+        }
+
+        if moves > 0 {
+            if let Some(handler) = *CONFIG.scroll_handler.lock() {
+                let total = self.above_buffer.len() + self.below_buffer.len();
+                handler(self.below_buffer.len(), total);
+            }
+        }
+
+        moves
+    }
+}
+
+fn mark_urls(row: &mut [Cell]) {
+    row.iter_mut()
+        .for_each(|cell| cell.flags.remove(Flags::URL));
+
+    let starts_with = |row: &[Cell], prefix: &str| {
+        let mut contents = row.iter().map(|cell| cell.content);
+        prefix.chars().all(|c| contents.next() == Some(c))
+    };
+
+    let mut column = 0;
+    while column < row.len() {
+        if starts_with(&row[column..], "http://") || starts_with(&row[column..], "https://") {
+            let length = row[column..]
+                .iter()
+                .position(|cell| cell.content.is_whitespace())
+                .unwrap_or(row.len() - column);
+            for cell in &mut row[column..column + length] {
+                cell.flags.insert(Flags::URL);
             }
+            column += length;
+        } else {
+            column += 1;
         }
     }
 }
@@ -1,9 +1,11 @@
+use core::fmt;
+
 use crate::{color::Color, config::CONFIG};
 use unicode_width::UnicodeWidthChar;
 
 bitflags::bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-    pub struct Flags: u8 {
+    pub struct Flags: u16 {
         const INVERSE = 1 << 0;
         const BOLD = 1 << 1;
         const ITALIC = 1 << 2;
@@ -12,9 +14,38 @@ bitflags::bitflags! {
         const CURSOR_BLOCK = 1 << 5;
         const CURSOR_UNDERLINE = 1 << 6;
         const CURSOR_BEAM = 1 << 7;
+        const BLINK = 1 << 8;
+        const URL = 1 << 9;
+        const DOUBLE_UNDERLINE = 1 << 10;
+        const CURLY_UNDERLINE = 1 << 11;
+        const OVERLINE = 1 << 12;
+        const STRIKETHROUGH = 1 << 13;
     }
 }
 
+const FLAG_NAMES: [(Flags, char); 11] = [
+    (Flags::BOLD, 'B'),
+    (Flags::ITALIC, 'I'),
+    (Flags::UNDERLINE, 'U'),
+    (Flags::DOUBLE_UNDERLINE, 'D'),
+    (Flags::CURLY_UNDERLINE, 'C'),
+    (Flags::INVERSE, 'R'),
+    (Flags::HIDDEN, 'H'),
+    (Flags::BLINK, 'K'),
+    (Flags::STRIKETHROUGH, 'S'),
+    (Flags::OVERLINE, 'O'),
+    (Flags::URL, 'L'),
+];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LineAttr {
+    #[default]
+    Normal,
+    DoubleWidth,
+    DoubleHeightTop,
+    DoubleHeightBottom,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Cell {
     pub content: char,
@@ -25,6 +56,25 @@ pub struct Cell {
     pub background: Color,
 }
 
+impl fmt::Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.placeholder || self.content == ' ' {
+            f.write_str(".")?;
+        } else {
+            write!(f, "{}", self.content)?;
+        }
+
+        let mut flags = FLAG_NAMES
+            .iter()
+            .filter(|(flag, _)| self.flags.contains(*flag))
+            .peekable();
+        if flags.peek().is_some() {
+            f.write_str(":")?;
+        }
+        flags.try_for_each(|(_, name)| write!(f, "{}", name))
+    }
+}
+
 impl Cell {
     pub fn set_placeholder(mut self) -> Self {
         self.placeholder = true;
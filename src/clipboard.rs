@@ -0,0 +1,27 @@
+use alloc::string::String;
+
+pub trait ClipboardHandler: Send {
+    fn get_text(&mut self) -> Option<String>;
+    fn set_text(&mut self, text: String);
+
+    fn get_primary_selection(&mut self) -> Option<String> {
+        self.get_text()
+    }
+
+    fn set_primary_selection(&mut self, text: String) {
+        self.set_text(text);
+    }
+}
+
+pub struct NullClipboard;
+
+impl ClipboardHandler for NullClipboard {
+    fn get_text(&mut self) -> Option<String> {
+        log!("No clipboard configured, ignoring clipboard load");
+        None
+    }
+
+    fn set_text(&mut self, text: String) {
+        log!("No clipboard configured, ignoring {} bytes", text.len());
+    }
+}
@@ -35,18 +35,25 @@ impl Color {
     pub fn to_rgb(self) -> Rgb {
         match self {
             Self::Rgb(rgb) => rgb,
-            Self::Indexed(index) => {
-                let color_scheme = CONFIG.color_scheme.lock();
-                match index {
-                    256 => color_scheme.foreground,
-                    257 => color_scheme.background,
-                    index => color_scheme.ansi_colors[index as usize],
-                }
-            }
+            Self::Indexed(_) => resolve_color(self, &CONFIG.color_scheme.lock()),
         }
     }
 }
 
+pub fn resolve_color(color: Color, scheme: &ColorScheme) -> Rgb {
+    match color {
+        Color::Rgb(rgb) => rgb,
+        Color::Indexed(256) => scheme.foreground,
+        Color::Indexed(257) => scheme.background,
+        Color::Indexed(index) => scheme
+            .ansi_colors
+            .get(index as usize)
+            .copied()
+            .unwrap_or(scheme.foreground),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ColorScheme {
     pub foreground: Rgb,
     pub background: Rgb,
@@ -1,9 +1,17 @@
 use alloc::boxed::Box;
-use core::{fmt, sync::atomic::AtomicBool};
+use alloc::sync::Arc;
+use core::{fmt, sync::atomic::AtomicBool, time::Duration};
 use spin::{Lazy, Mutex};
 
+use crate::clipboard::{ClipboardHandler, NullClipboard};
 use crate::color::ColorScheme;
-use crate::font::FontManager;
+use crate::draw::HardwareCursor;
+use crate::font::{FontManager, SharedFontManager};
+use crate::image::ImageHandler;
+use crate::pty::PtyWriter;
+
+pub type ScrollHandler = fn(usize, usize);
+pub type DcsHandler = fn(&[u8]);
 
 pub static CONFIG: Lazy<TerminalConfig> = Lazy::new(TerminalConfig::default);
 
@@ -11,9 +19,20 @@ pub struct TerminalConfig {
     pub auto_flush: AtomicBool,
     pub logger: Mutex<Option<fn(fmt::Arguments)>>,
     pub font_manager: Mutex<Option<Box<dyn FontManager>>>,
+    pub shared_font_manager: Mutex<Option<SharedFontManager>>,
     pub color_scheme: Mutex<ColorScheme>,
     pub bell_handler: Mutex<Option<fn()>>,
+    pub scroll_handler: Mutex<Option<ScrollHandler>>,
     pub auto_crnl: AtomicBool,
+    pub tmux_passthrough: AtomicBool,
+    pub hardware_cursor: Mutex<Option<Box<dyn HardwareCursor>>>,
+    pub clock: Mutex<Option<fn() -> Duration>>,
+    pub clipboard: Mutex<Option<Box<dyn ClipboardHandler>>>,
+    pub url_handler: Mutex<Option<fn(&str)>>,
+    pub title_handler: Mutex<Option<fn(&str)>>,
+    pub dcs_handler: Mutex<Option<DcsHandler>>,
+    pub pty_writer: Mutex<Option<Box<dyn PtyWriter>>>,
+    pub image_handler: Mutex<Option<Box<dyn ImageHandler>>>,
 }
 
 impl Default for TerminalConfig {
@@ -22,9 +41,32 @@ impl Default for TerminalConfig {
             auto_flush: AtomicBool::new(true),
             logger: Mutex::new(None),
             font_manager: Mutex::new(None),
+            shared_font_manager: Mutex::new(None),
             color_scheme: Mutex::new(ColorScheme::default()),
             bell_handler: Mutex::new(None),
+            scroll_handler: Mutex::new(None),
             auto_crnl: AtomicBool::new(true),
+            tmux_passthrough: AtomicBool::new(false),
+            hardware_cursor: Mutex::new(None),
+            clock: Mutex::new(None),
+            clipboard: Mutex::new(Some(Box::new(NullClipboard))),
+            url_handler: Mutex::new(None),
+            title_handler: Mutex::new(None),
+            dcs_handler: Mutex::new(None),
+            pty_writer: Mutex::new(None),
+            image_handler: Mutex::new(None),
+        }
+    }
+}
+
+impl TerminalConfig {
+    pub fn with_font_manager<R>(&self, f: impl FnOnce(&mut dyn FontManager) -> R) -> Option<R> {
+        if let Some(shared) = self.shared_font_manager.lock().as_ref().map(Arc::clone) {
+            return Some(f(&mut *shared.lock()));
         }
+        let mut font_manager = self.font_manager.lock();
+        font_manager
+            .as_mut()
+            .map(|font_manager| f(font_manager.as_mut()))
     }
 }
@@ -0,0 +1,16 @@
+use crate::color::Rgb;
+
+pub trait DrawTarget: Send {
+    fn size(&self) -> (usize, usize);
+    fn draw_pixel(&mut self, x: usize, y: usize, color: Rgb);
+}
+
+#[inline(always)]
+pub fn rgb_to_pixel(color: Rgb) -> u32 {
+    (color.0 as u32) << 16 | (color.1 as u32) << 8 | color.2 as u32
+}
+
+pub trait HardwareCursor: Send {
+    fn set_cursor_position(&mut self, x: usize, y: usize);
+    fn set_cursor_visible(&mut self, visible: bool);
+}
@@ -1,3 +1,4 @@
+use alloc::vec::Vec;
 use noto_sans_mono_bitmap::{get_raster, get_raster_width};
 use noto_sans_mono_bitmap::{FontWeight, RasterHeight};
 
@@ -8,12 +9,94 @@ const FONT_HEIGHT: RasterHeight = RasterHeight::Size20;
 
 pub struct BitmapFont;
 
+pub struct AtlasBitmapFont {
+    font_data: &'static [u8],
+    width: usize,
+    height: usize,
+    first_char: u32,
+    glyph_count: u32,
+}
+
+impl AtlasBitmapFont {
+    pub fn from_bytes(
+        font_data: &'static [u8],
+        width: usize,
+        height: usize,
+        first_char: u32,
+        glyph_count: u32,
+    ) -> Self {
+        let font = Self {
+            font_data,
+            width,
+            height,
+            first_char,
+            glyph_count,
+        };
+        assert!(
+            font_data.len() >= font.glyph_size() * glyph_count as usize,
+            "font data is too short for {} glyphs",
+            glyph_count
+        );
+        font
+    }
+
+    fn glyph_size(&self) -> usize {
+        (self.width * self.height).div_ceil(8)
+    }
+
+    fn glyph(&self, content: char) -> Option<&'static [u8]> {
+        let index = (content as u32).checked_sub(self.first_char)?;
+        if index >= self.glyph_count {
+            return None;
+        }
+        let start = index as usize * self.glyph_size();
+        Some(&self.font_data[start..start + self.glyph_size()])
+    }
+
+    fn rasterize_glyph(&self, info: &ContentInfo) -> Vec<Vec<u8>> {
+        let mut raster = vec![vec![0u8; self.width]; self.height];
+
+        let glyph = self
+            .glyph(info.content)
+            .or_else(|| self.glyph('\u{fffd}'))
+            .or_else(|| self.glyph('?'));
+
+        if let Some(glyph) = glyph {
+            for (y, line) in raster.iter_mut().enumerate() {
+                for (x, pixel) in line.iter_mut().enumerate() {
+                    let bit = y * self.width + x;
+                    if glyph[bit / 8] & (0x80 >> (bit % 8)) != 0 {
+                        *pixel = 0xff;
+                    }
+                }
+                if info.bold {
+                    for x in (1..self.width).rev() {
+                        line[x] |= line[x - 1];
+                    }
+                }
+            }
+        }
+
+        raster
+    }
+}
+
+impl FontManager for AtlasBitmapFont {
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn rasterize(&mut self, info: ContentInfo) -> Rasterized<'_> {
+        Rasterized::Owned(self.rasterize_glyph(&info))
+    }
+}
+
 impl FontManager for BitmapFont {
     fn size(&self) -> (usize, usize) {
         (FONT_WIDTH, FONT_HEIGHT as usize)
     }
 
-    fn rasterize(&mut self, info: ContentInfo) -> Rasterized {
+    fn rasterize(&mut self, info: ContentInfo) -> Rasterized<'_> {
         let font_weight = if info.bold {
             FontWeight::Bold
         } else {
@@ -1,4 +1,6 @@
+use alloc::sync::Arc;
 use alloc::vec::Vec;
+use spin::Mutex;
 
 #[cfg(feature = "bitmap")]
 mod bitmap;
@@ -6,17 +8,21 @@ mod bitmap;
 mod truetype;
 
 #[cfg(feature = "bitmap")]
-pub use bitmap::BitmapFont;
+pub use bitmap::{AtlasBitmapFont, BitmapFont};
 #[cfg(feature = "truetype")]
 pub use truetype::TrueTypeFont;
 
+pub type SharedFontManager = Arc<Mutex<dyn FontManager>>;
+
 pub enum Rasterized<'a> {
     Slice(&'a [&'a [u8]]),
+    Flat(&'a [u8], usize),
     Vec(&'a Vec<Vec<u8>>),
     Owned(Vec<Vec<u8>>),
+    Rgba(&'a Vec<Vec<[u8; 4]>>),
 }
 
-#[derive(Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ContentInfo {
     pub content: char,
     pub bold: bool,
@@ -37,5 +43,7 @@ impl ContentInfo {
 
 pub trait FontManager: Send {
     fn size(&self) -> (usize, usize);
-    fn rasterize(&mut self, info: ContentInfo) -> Rasterized;
+    fn rasterize(&mut self, info: ContentInfo) -> Rasterized<'_>;
+
+    fn pre_warm(&mut self, _chars: &[char]) {}
 }
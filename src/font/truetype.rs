@@ -1,8 +1,25 @@
-use ab_glyph::{Font, FontRef, PxScale, ScaleFont, VariableFont};
+use ab_glyph::{Font, FontRef, GlyphImageFormat, PxScale, ScaleFont, VariableFont};
 use alloc::{collections::BTreeMap, vec::Vec};
+use unicode_width::UnicodeWidthChar;
 
 use super::{ContentInfo, FontManager, Rasterized};
 
+const DEFAULT_CACHE_SIZE: usize = 512;
+
+enum CachedGlyph {
+    Outline(Vec<Vec<u8>>),
+    Color(Vec<Vec<[u8; 4]>>),
+}
+
+impl CachedGlyph {
+    fn memory_size(&self) -> usize {
+        match self {
+            Self::Outline(bitmap) => bitmap.iter().map(Vec::len).sum(),
+            Self::Color(bitmap) => bitmap.iter().map(|row| row.len() * 4).sum(),
+        }
+    }
+}
+
 pub struct TrueTypeFont {
     font: FontRef<'static>,
     italic_font: Option<FontRef<'static>>,
@@ -10,35 +27,182 @@ pub struct TrueTypeFont {
     raster_width: usize,
     font_size: PxScale,
     base_line_offset: f32,
-    bitmap_cache: BTreeMap<ContentInfo, Vec<Vec<u8>>>,
+    normal_weight: f32,
+    bold_weight: f32,
+    italic_slant: Option<f32>,
+    bitmap_cache: BTreeMap<ContentInfo, (u64, CachedGlyph)>,
+    cache_order: BTreeMap<u64, ContentInfo>,
+    cache_size: usize,
+    cache_memory: usize,
+    cache_memory_limit: usize,
+    cache_clock: u64,
 }
 
 impl TrueTypeFont {
     pub fn new(font_size: f32, font_bytes: &'static [u8]) -> Self {
-        let font = FontRef::try_from_slice(font_bytes).unwrap();
-        let font_size = font.pt_to_px_scale(font_size).unwrap();
+        let mut truetype = Self {
+            font: FontRef::try_from_slice(font_bytes).unwrap(),
+            italic_font: None,
+            raster_height: 0,
+            raster_width: 0,
+            font_size: PxScale::from(0.0),
+            base_line_offset: 0.0,
+            normal_weight: 400.0,
+            bold_weight: 700.0,
+            italic_slant: None,
+            bitmap_cache: BTreeMap::new(),
+            cache_order: BTreeMap::new(),
+            cache_size: DEFAULT_CACHE_SIZE,
+            cache_memory: 0,
+            cache_memory_limit: usize::MAX,
+            cache_clock: 0,
+        };
+        truetype.set_font_size(font_size);
+        truetype
+    }
 
-        let line_height = font.as_scaled(font_size).height();
-        let base_line_offset = font.as_scaled(font_size).ascent();
+    pub fn set_font_size(&mut self, font_size: f32) {
+        let font_size = self.font.pt_to_px_scale(font_size).unwrap();
+        let scaled_font = self.font.as_scaled(font_size);
+        let line_height = scaled_font.height();
 
-        let raster_height = line_height as usize;
-        let raster_width = (line_height / 2.0) as usize;
+        let raster_height = (line_height as usize).max(1);
+        let raster_width = ((line_height / 2.0) as usize).max(1);
 
-        Self {
-            font,
-            italic_font: None,
-            raster_height,
-            raster_width,
-            font_size,
-            base_line_offset,
-            bitmap_cache: BTreeMap::new(),
+        self.base_line_offset = scaled_font.ascent();
+        self.font_size = font_size;
+        self.raster_height = raster_height;
+        self.raster_width = raster_width;
+        self.bitmap_cache.clear();
+        self.cache_order.clear();
+        self.cache_memory = 0;
+    }
+
+    fn rasterize_outline(&mut self, info: &ContentInfo) -> Vec<Vec<u8>> {
+        let select_font = if info.italic {
+            self.italic_font.as_mut().unwrap_or(&mut self.font)
+        } else {
+            &mut self.font
+        };
+
+        let font_weight = if info.bold {
+            self.bold_weight
+        } else {
+            self.normal_weight
+        };
+        select_font.set_variation(b"wght", font_weight);
+
+        if let Some(slant) = self.italic_slant {
+            let (ital, slnt) = if info.italic {
+                (1.0, slant)
+            } else {
+                (0.0, 0.0)
+            };
+            select_font.set_variation(b"ital", ital);
+            select_font.set_variation(b"slnt", slnt);
+        }
+
+        let glyph_id = select_font.glyph_id(info.content);
+        let glyph = glyph_id.with_scale(self.font_size);
+
+        let actual_width = self.raster_width * if info.wide { 2 } else { 1 };
+        let mut letter_bitmap = vec![vec![0u8; actual_width]; self.raster_height];
+
+        let h_advance = select_font.as_scaled(self.font_size).h_advance(glyph_id);
+        let advance_diff = actual_width as f32 - h_advance;
+        let center_offset = if advance_diff.abs() > 1.0 {
+            advance_diff / 2.0
+        } else {
+            0.0
+        };
+
+        if let Some(bitmap) = select_font.outline_glyph(glyph) {
+            let px_bounds = bitmap.px_bounds();
+
+            let x_offset = (px_bounds.min.x + center_offset) as isize;
+            let y_offset = (self.base_line_offset + px_bounds.min.y) as isize;
+
+            bitmap.draw(|x, y, c| {
+                let x = x_offset + x as isize;
+                let y = y_offset + y as isize;
+
+                if (x >= 0 && x < actual_width as isize)
+                    && (y >= 0 && y < self.raster_height as isize)
+                {
+                    letter_bitmap[y as usize][x as usize] = (c * 255.0) as u8;
+                }
+            });
+        }
+
+        letter_bitmap
+    }
+
+    fn color_bitmap(&self, info: &ContentInfo) -> Option<Vec<Vec<[u8; 4]>>> {
+        let glyph_id = self.font.glyph_id(info.content);
+        let image = self
+            .font
+            .glyph_raster_image2(glyph_id, self.raster_height as u16)?;
+
+        if !matches!(image.format, GlyphImageFormat::BitmapPremulBgra32) {
+            return None;
+        }
+
+        let (width, height) = (image.width as usize, image.height as usize);
+        if width == 0 || height == 0 || image.data.len() < width * height * 4 {
+            return None;
         }
+
+        let actual_width = self.raster_width * if info.wide { 2 } else { 1 };
+        let bitmap = (0..self.raster_height)
+            .map(|y| {
+                (0..actual_width)
+                    .map(|x| {
+                        let (image_x, image_y) =
+                            (x * width / actual_width, y * height / self.raster_height);
+                        let offset = (image_y * width + image_x) * 4;
+                        let [b, g, r, a] = [0, 1, 2, 3].map(|i| image.data[offset + i]);
+                        if a == 0 {
+                            return [0; 4];
+                        }
+                        let unpremultiply = |c: u8| (c as u16 * 0xff / a as u16).min(0xff) as u8;
+                        [unpremultiply(r), unpremultiply(g), unpremultiply(b), a]
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Some(bitmap)
     }
 
     pub fn with_italic_font(mut self, italic_font: &'static [u8]) -> Self {
         self.italic_font = Some(FontRef::try_from_slice(italic_font).unwrap());
         self
     }
+
+    pub fn with_cache_size(mut self, size: usize) -> Self {
+        self.cache_size = size.max(1);
+        self
+    }
+
+    pub fn with_cache_memory_limit(mut self, bytes: usize) -> Self {
+        self.cache_memory_limit = bytes;
+        self
+    }
+
+    pub fn with_normal_weight(mut self, weight: f32) -> Self {
+        self.normal_weight = weight;
+        self
+    }
+
+    pub fn with_bold_weight(mut self, weight: f32) -> Self {
+        self.bold_weight = weight;
+        self
+    }
+
+    pub fn with_italic_slant(mut self, degrees: f32) -> Self {
+        self.italic_slant = Some(degrees);
+        self
+    }
 }
 
 impl FontManager for TrueTypeFont {
@@ -46,42 +210,48 @@ impl FontManager for TrueTypeFont {
         (self.raster_width, self.raster_height)
     }
 
-    fn rasterize(&mut self, info: ContentInfo) -> Rasterized {
-        Rasterized::Vec(self.bitmap_cache.entry(info.clone()).or_insert_with(|| {
-            let select_font = if info.italic {
-                self.italic_font.as_mut().unwrap_or(&mut self.font)
-            } else {
-                &mut self.font
+    fn rasterize(&mut self, info: ContentInfo) -> Rasterized<'_> {
+        self.cache_clock += 1;
+        if let Some((last_used, _)) = self.bitmap_cache.get_mut(&info) {
+            self.cache_order.remove(last_used);
+            *last_used = self.cache_clock;
+        } else {
+            let glyph = match self.color_bitmap(&info) {
+                Some(bitmap) => CachedGlyph::Color(bitmap),
+                None => CachedGlyph::Outline(self.rasterize_outline(&info)),
             };
+            let memory_size = glyph.memory_size();
 
-            let font_weight = if info.bold { 700.0 } else { 400.0 };
-            select_font.set_variation(b"wght", font_weight);
-
-            let glyph_id = select_font.glyph_id(info.content);
-            let glyph = glyph_id.with_scale(self.font_size);
-
-            let actual_width = self.raster_width * if info.wide { 2 } else { 1 };
-            let mut letter_bitmap = vec![vec![0u8; actual_width]; self.raster_height];
-
-            if let Some(bitmap) = select_font.outline_glyph(glyph) {
-                let px_bounds = bitmap.px_bounds();
+            while self.bitmap_cache.len() >= self.cache_size
+                || self.cache_memory + memory_size > self.cache_memory_limit
+            {
+                let Some((_, oldest)) = self.cache_order.pop_first() else {
+                    break;
+                };
+                if let Some((_, glyph)) = self.bitmap_cache.remove(&oldest) {
+                    self.cache_memory -= glyph.memory_size();
+                }
+            }
 
-                let x_offset = px_bounds.min.x as isize;
-                let y_offset = (self.base_line_offset + px_bounds.min.y) as isize;
+            self.cache_memory += memory_size;
+            self.bitmap_cache
+                .insert(info.clone(), (self.cache_clock, glyph));
+        }
+        self.cache_order.insert(self.cache_clock, info.clone());
 
-                bitmap.draw(|x, y, c| {
-                    let x = x_offset + x as isize;
-                    let y = y_offset + y as isize;
+        let (_, glyph) = self.bitmap_cache.get(&info).unwrap();
+        match glyph {
+            CachedGlyph::Outline(bitmap) => Rasterized::Vec(bitmap),
+            CachedGlyph::Color(bitmap) => Rasterized::Rgba(bitmap),
+        }
+    }
 
-                    if (x >= 0 && x < actual_width as isize)
-                        && (y >= 0 && y < self.raster_height as isize)
-                    {
-                        letter_bitmap[y as usize][x as usize] = (c * 255.0) as u8;
-                    }
-                });
+    fn pre_warm(&mut self, chars: &[char]) {
+        for &content in chars {
+            let wide = content.width().unwrap_or(0) > 1;
+            for (bold, italic) in [(false, false), (true, false), (false, true), (true, true)] {
+                self.rasterize(ContentInfo::new(content, bold, italic, wide));
             }
-
-            letter_bitmap
-        }))
+        }
     }
 }
@@ -1,45 +1,168 @@
 use alloc::collections::btree_map::BTreeMap;
 use core::mem::swap;
+use core::time::Duration;
 
-use crate::cell::{Cell, Flags};
+use crate::cell::{Cell, Flags, LineAttr};
 use crate::color::Rgb;
 use crate::config::CONFIG;
+use crate::draw::DrawTarget;
 use crate::font::{ContentInfo, Rasterized};
 
-pub trait DrawTarget {
-    fn size(&self) -> (usize, usize);
-    fn draw_pixel(&mut self, x: usize, y: usize, color: Rgb);
+type FgBgPair = (Rgb, Rgb);
+
+const DEFAULT_COLOR_CACHE_SIZE: usize = 128;
+const COLOR_CACHE_LOG_INTERVAL: u64 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecorationStyle {
+    Underline,
+    DoubleUnderline,
+    CurlyUnderline,
+    Overline,
+    Strikethrough,
 }
 
-type FgBgPair = (Rgb, Rgb);
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RenderStats {
+    pub total_flushes: u64,
+    pub total_cells_drawn: u64,
+    pub total_cache_hits: u64,
+    pub total_cache_misses: u64,
+    pub last_flush_duration_us: u64,
+    pub avg_flush_duration_us: u64,
+    pub color_cache: Option<ColorCacheStats>,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ColorCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub writes: u64,
+}
 
 pub struct Graphic<D: DrawTarget> {
     graphic: D,
+    cached_size: (usize, usize),
     color_cache: BTreeMap<FgBgPair, ColorCache>,
+    color_cache_size: usize,
+    color_cache_clock: u64,
+    color_cache_stats: Option<ColorCacheStats>,
+    logging: bool,
+    stats: RenderStats,
+    total_flush_duration_us: u64,
 }
 
 impl<D: DrawTarget> Graphic<D> {
     #[inline]
     pub fn width(&self) -> usize {
-        self.graphic.size().0
+        self.cached_size.0
     }
 
     #[inline]
     pub fn height(&self) -> usize {
-        self.graphic.size().1
+        self.cached_size.1
     }
 
     #[inline]
     pub fn size(&self) -> (usize, usize) {
-        self.graphic.size()
+        self.cached_size
+    }
+
+    pub fn update_size(&mut self) {
+        self.cached_size = self.graphic.size();
     }
 }
 
 impl<D: DrawTarget> Graphic<D> {
     pub fn new(graphic: D) -> Self {
         Self {
+            cached_size: graphic.size(),
             graphic,
             color_cache: BTreeMap::new(),
+            color_cache_size: DEFAULT_COLOR_CACHE_SIZE,
+            color_cache_clock: 0,
+            color_cache_stats: None,
+            logging: false,
+            stats: RenderStats::default(),
+            total_flush_duration_us: 0,
+        }
+    }
+
+    pub fn draw_target(&mut self) -> &mut D {
+        &mut self.graphic
+    }
+
+    pub fn stats(&self) -> RenderStats {
+        RenderStats {
+            color_cache: self.color_cache_stats,
+            ..self.stats
+        }
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = RenderStats::default();
+        self.color_cache_stats = None;
+        self.total_flush_duration_us = 0;
+    }
+
+    pub fn set_color_cache_size(&mut self, size: usize) {
+        self.color_cache_size = size.max(1);
+        self.color_cache_stats = None;
+        while self.color_cache.len() > self.color_cache_size {
+            self.evict_color_cache();
+        }
+    }
+
+    fn evict_color_cache(&mut self) {
+        let oldest = self
+            .color_cache
+            .iter()
+            .min_by_key(|(_, cache)| cache.last_used)
+            .map(|(&key, _)| key);
+        if let Some(key) = oldest {
+            self.color_cache.remove(&key);
+        }
+    }
+
+    fn record_color_cache(&mut self, hit: bool) {
+        if !self.logging {
+            return;
+        }
+
+        let stats = self.color_cache_stats.get_or_insert_with(Default::default);
+        if hit {
+            stats.hits += 1;
+        } else {
+            stats.misses += 1;
+        }
+
+        stats.writes += 1;
+        if stats.writes.is_multiple_of(COLOR_CACHE_LOG_INTERVAL) {
+            let (hits, misses) = (stats.hits, stats.misses);
+            log!(
+                "color_cache: hits={}, misses={}, size={}",
+                hits,
+                misses,
+                self.color_cache.len()
+            );
+        }
+    }
+
+    pub fn begin_flush(&mut self) -> Option<Duration> {
+        self.logging = CONFIG.logger.lock().is_some();
+        current_time()
+    }
+
+    pub fn end_flush(&mut self, start_time: Option<Duration>) {
+        self.stats.total_flushes += 1;
+
+        if let Some(start_time) = start_time {
+            let end_time = current_time().unwrap_or(start_time);
+            let duration = end_time.saturating_sub(start_time).as_micros() as u64;
+            self.total_flush_duration_us += duration;
+            self.stats.last_flush_duration_us = duration;
+            self.stats.avg_flush_duration_us =
+                self.total_flush_duration_us / self.stats.total_flushes;
         }
     }
 
@@ -49,8 +172,17 @@ impl<D: DrawTarget> Graphic<D> {
         end: impl Into<(usize, usize)>,
         cell: Cell,
     ) {
-        let color = cell.background.to_rgb();
+        self.fill(start, end, cell.background.to_rgb());
+    }
+
+    pub fn fill(
+        &mut self,
+        start: impl Into<(usize, usize)>,
+        end: impl Into<(usize, usize)>,
+        color: Rgb,
+    ) {
         let (start, end) = (start.into(), end.into());
+        let end = (end.0.min(self.cached_size.0), end.1.min(self.cached_size.1));
 
         for y in start.1..end.1 {
             for x in start.0..end.0 {
@@ -59,7 +191,7 @@ impl<D: DrawTarget> Graphic<D> {
         }
     }
 
-    pub fn write(&mut self, row: usize, col: usize, cell: Cell) {
+    pub fn write(&mut self, row: usize, col: usize, cell: Cell, line_attr: LineAttr) {
         if cell.placeholder {
             return;
         }
@@ -75,14 +207,34 @@ impl<D: DrawTarget> Graphic<D> {
             foreground = background;
         }
 
+        self.stats.total_cells_drawn += 1;
+        let hit = self.color_cache.contains_key(&(foreground, background));
+        if hit {
+            self.stats.total_cache_hits += 1;
+        } else {
+            self.stats.total_cache_misses += 1;
+            if self.color_cache.len() >= self.color_cache_size {
+                self.evict_color_cache();
+            }
+        }
+        self.record_color_cache(hit);
+
+        self.color_cache_clock += 1;
         let color_cache = self
             .color_cache
             .entry((foreground, background))
             .or_insert_with(|| ColorCache::new(foreground, background));
+        color_cache.last_used = self.color_cache_clock;
+        let decoration_color = color_cache.colors[0xff];
+        let (screen_width, screen_height) = self.cached_size;
 
-        if let Some(font_manager) = CONFIG.font_manager.lock().as_mut() {
+        let Some(cell_area) = CONFIG.with_font_manager(|font_manager| {
+            let x_scale = if line_attr == LineAttr::Normal { 1 } else { 2 };
             let (font_width, font_height) = font_manager.size();
+            let font_width = font_width * x_scale;
             let (x_start, y_start) = (col * font_width, row * font_height);
+            let visible_width = screen_width.saturating_sub(x_start);
+            let visible_height = screen_height.saturating_sub(y_start);
 
             let content_info = ContentInfo::new(
                 cell.content,
@@ -91,44 +243,150 @@ impl<D: DrawTarget> Graphic<D> {
                 cell.wide,
             );
 
+            let source_row = |y: usize, height: usize| match line_attr {
+                LineAttr::DoubleHeightTop => y / 2,
+                LineAttr::DoubleHeightBottom => (y + height) / 2,
+                _ => y,
+            };
+
             macro_rules! draw_raster {
-                ($raster:ident) => {
-                    for (y, lines) in $raster.iter().enumerate() {
-                        for (x, &intensity) in lines.iter().enumerate() {
-                            let (r, g, b) = color_cache.colors[intensity as usize];
-                            self.graphic.draw_pixel(x_start + x, y_start + y, (r, g, b));
+                ($raster:ident, $color:expr) => {
+                    draw_raster!($raster.len(), |row| &$raster[row], $color)
+                };
+                ($height:expr, |$row:ident| $lines:expr, $color:expr) => {{
+                    let height = $height;
+                    for y in 0..height.min(visible_height) {
+                        let $row = source_row(y, height);
+                        let lines = $lines;
+                        for x in 0..(lines.len() * x_scale).min(visible_width) {
+                            let color = $color(lines[x / x_scale]);
+                            self.graphic.draw_pixel(x_start + x, y_start + y, color);
                         }
                     }
-                };
+                }};
             }
 
+            let intensity_color = |intensity: u8| color_cache.colors[intensity as usize];
+            let blend_color = |[r, g, b, a]: [u8; 4]| {
+                let blend = |color: u8, background: u8| {
+                    let (color, background, a) = (color as u16, background as u16, a as u16);
+                    ((color * a + background * (0xff - a)) / 0xff) as u8
+                };
+                (
+                    blend(r, background.0),
+                    blend(g, background.1),
+                    blend(b, background.2),
+                )
+            };
+
             match font_manager.rasterize(content_info) {
-                Rasterized::Slice(raster) => draw_raster!(raster),
-                Rasterized::Vec(raster) => draw_raster!(raster),
-                Rasterized::Owned(raster) => draw_raster!(raster),
+                Rasterized::Slice(raster) => draw_raster!(raster, intensity_color),
+                Rasterized::Flat(data, width) => draw_raster!(
+                    data.len().checked_div(width).unwrap_or(0),
+                    |row| &data[row * width..(row + 1) * width],
+                    intensity_color
+                ),
+                Rasterized::Vec(raster) => draw_raster!(raster, intensity_color),
+                Rasterized::Owned(raster) => draw_raster!(raster, intensity_color),
+                Rasterized::Rgba(raster) => draw_raster!(raster, blend_color),
             }
 
-            if cell.flags.contains(Flags::CURSOR_BEAM) {
-                for y in 0..font_height {
-                    let (r, g, b) = color_cache.colors[0xff];
-                    self.graphic.draw_pixel(x_start, y_start + y, (r, g, b));
+            if cell.flags.contains(Flags::CURSOR_BEAM) && visible_width > 0 {
+                for y in 0..font_height.min(visible_height) {
+                    self.graphic
+                        .draw_pixel(x_start, y_start + y, decoration_color);
                 }
             }
 
-            if cell.flags.contains(Flags::UNDERLINE) || cell.flags.contains(Flags::CURSOR_UNDERLINE)
-            {
+            (x_start, y_start, font_width, font_height)
+        }) else {
+            return;
+        };
+
+        let decorations = [
+            (
+                Flags::UNDERLINE | Flags::CURSOR_UNDERLINE | Flags::URL,
+                DecorationStyle::Underline,
+            ),
+            (Flags::DOUBLE_UNDERLINE, DecorationStyle::DoubleUnderline),
+            (Flags::CURLY_UNDERLINE, DecorationStyle::CurlyUnderline),
+            (Flags::OVERLINE, DecorationStyle::Overline),
+            (Flags::STRIKETHROUGH, DecorationStyle::Strikethrough),
+        ];
+
+        let (x_start, y_start, font_width, font_height) = cell_area;
+        for (flags, style) in decorations {
+            if cell.flags.intersects(flags) {
+                self.draw_decoration(
+                    x_start,
+                    y_start,
+                    font_width,
+                    font_height,
+                    style,
+                    decoration_color,
+                );
+            }
+        }
+    }
+
+    pub fn draw_decoration(
+        &mut self,
+        x_start: usize,
+        y_start: usize,
+        font_width: usize,
+        font_height: usize,
+        style: DecorationStyle,
+        color: Rgb,
+    ) {
+        if font_height == 0 {
+            return;
+        }
+        let bottom = font_height - 1;
+        let (screen_width, screen_height) = self.cached_size;
+        let font_width = font_width.min(screen_width.saturating_sub(x_start));
+        let visible = |y: usize| y_start + y < screen_height;
+
+        let mut draw_line = |y: usize| {
+            if !visible(y) {
+                return;
+            }
+            for x in 0..font_width {
+                self.graphic.draw_pixel(x_start + x, y_start + y, color);
+            }
+        };
+
+        match style {
+            DecorationStyle::Underline => draw_line(bottom),
+            DecorationStyle::DoubleUnderline => {
+                draw_line(bottom);
+                draw_line(bottom.saturating_sub(2));
+            }
+            DecorationStyle::Overline => draw_line(0),
+            DecorationStyle::Strikethrough => draw_line(font_height / 2),
+            DecorationStyle::CurlyUnderline => {
                 for x in 0..font_width {
-                    let (r, g, b) = color_cache.colors[0xff];
-                    self.graphic
-                        .draw_pixel(x_start + x, y_start + font_height - 1, (r, g, b));
+                    let offset = match (x / 2) % 4 {
+                        0 => 0,
+                        2 => 2,
+                        _ => 1,
+                    };
+                    let y = bottom.saturating_sub(offset);
+                    if visible(y) {
+                        self.graphic.draw_pixel(x_start + x, y_start + y, color);
+                    }
                 }
             }
         }
     }
 }
 
-struct ColorCache {
+fn current_time() -> Option<Duration> {
+    CONFIG.clock.lock().map(|clock| clock())
+}
+
+pub struct ColorCache {
     colors: [Rgb; 256],
+    last_used: u64,
 }
 
 impl ColorCache {
@@ -148,6 +406,9 @@ impl ColorCache {
             )
         });
 
-        Self { colors }
+        Self {
+            colors,
+            last_used: 0,
+        }
     }
 }
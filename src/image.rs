@@ -0,0 +1,97 @@
+use crate::draw::DrawTarget;
+
+pub const MAX_IMAGE_SIZE: usize = 1 << 24;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Rgb,
+    Rgba,
+    Png,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ImagePlacement {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+    pub columns: usize,
+    pub rows: usize,
+    pub format: ImageFormat,
+}
+
+pub trait ImageHandler: Send {
+    fn render_image(
+        &mut self,
+        id: u32,
+        data: &[u8],
+        placement: ImagePlacement,
+        target: &mut dyn DrawTarget,
+    );
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct KittyCommand {
+    pub action: u8,
+    pub id: u32,
+    pub format: ImageFormat,
+    pub width: usize,
+    pub height: usize,
+    pub columns: usize,
+    pub rows: usize,
+    pub more: bool,
+    pub quiet: u8,
+    pub keep_cursor: bool,
+}
+
+impl KittyCommand {
+    pub fn parse(control: &[u8]) -> Self {
+        let mut command = Self {
+            action: b't',
+            id: 0,
+            format: ImageFormat::Rgba,
+            width: 0,
+            height: 0,
+            columns: 0,
+            rows: 0,
+            more: false,
+            quiet: 0,
+            keep_cursor: false,
+        };
+
+        for pair in control.split(|&byte| byte == b',') {
+            let Some((&key, value)) = pair.split_first() else {
+                continue;
+            };
+            let Some(value) = value.strip_prefix(b"=") else {
+                continue;
+            };
+            let number = core::str::from_utf8(value)
+                .ok()
+                .and_then(|value| value.parse::<u32>().ok())
+                .unwrap_or(0);
+
+            match key {
+                b'a' => command.action = value.first().copied().unwrap_or(b't'),
+                b'i' => command.id = number,
+                b'f' => {
+                    command.format = match number {
+                        24 => ImageFormat::Rgb,
+                        100 => ImageFormat::Png,
+                        _ => ImageFormat::Rgba,
+                    }
+                }
+                b's' => command.width = number as usize,
+                b'v' => command.height = number as usize,
+                b'c' => command.columns = number as usize,
+                b'r' => command.rows = number as usize,
+                b'm' => command.more = number == 1,
+                b'q' => command.quiet = number as u8,
+                b'C' => command.keep_cursor = number == 1,
+                _ => {}
+            }
+        }
+
+        command
+    }
+}
@@ -0,0 +1,208 @@
+use alloc::vec::Vec;
+use vte::ansi::CharsetIndex;
+
+use crate::cell::LineAttr;
+
+const ESC: u8 = 0x1b;
+const MAX_PAYLOAD_SIZE: usize = 1 << 20;
+
+pub enum Sequence {
+    Byte(u8),
+    Escape(u8),
+    EscapeHash(u8),
+    Dcs(Vec<u8>),
+    Apc(Vec<u8>),
+    SingleShift(CharsetIndex),
+    LockingShift(CharsetIndex),
+    LockingShiftRight(CharsetIndex),
+    LineAttr(LineAttr),
+    Vt52(u8),
+    Vt52Goto(usize, usize),
+    SoftReset,
+    Index,
+}
+
+#[derive(Clone, Copy)]
+enum PayloadKind {
+    Dcs,
+    Apc,
+}
+
+#[derive(Default)]
+enum State {
+    #[default]
+    Ground,
+    Escape,
+    EscapeHash,
+    Payload(PayloadKind),
+    PayloadEscape(PayloadKind),
+    Csi,
+    CsiBang,
+    Vt52Row,
+    Vt52Column(u8),
+}
+
+#[derive(Default)]
+pub struct Interceptor {
+    state: State,
+    payload: Vec<u8>,
+    vt52: bool,
+}
+
+impl Interceptor {
+    pub fn set_vt52(&mut self, vt52: bool) {
+        self.vt52 = vt52;
+    }
+
+    pub fn advance(&mut self, byte: u8) -> Option<Sequence> {
+        match self.state {
+            State::Ground => {
+                if byte == ESC {
+                    self.state = State::Escape;
+                    return None;
+                }
+                Some(Sequence::Byte(byte))
+            }
+            State::Escape if self.vt52 => {
+                self.state = State::Ground;
+                match byte {
+                    ESC => Some(Sequence::Byte(ESC)),
+                    b'Y' => {
+                        self.state = State::Vt52Row;
+                        None
+                    }
+                    _ => Some(Sequence::Vt52(byte)),
+                }
+            }
+            State::Vt52Row => {
+                self.state = State::Vt52Column(byte);
+                None
+            }
+            State::Vt52Column(row) => {
+                self.state = State::Ground;
+                let (row, column) = (row.saturating_sub(0x20), byte.saturating_sub(0x20));
+                Some(Sequence::Vt52Goto(row as usize, column as usize))
+            }
+            State::Escape => match byte {
+                ESC => Some(Sequence::Byte(ESC)),
+                b'P' | b'_' => {
+                    self.state = State::Payload(match byte {
+                        b'P' => PayloadKind::Dcs,
+                        _ => PayloadKind::Apc,
+                    });
+                    self.payload.clear();
+                    None
+                }
+                b'#' => {
+                    self.state = State::EscapeHash;
+                    None
+                }
+                b'[' => {
+                    self.state = State::Csi;
+                    Some(Sequence::Escape(byte))
+                }
+                b'n' | b'o' => {
+                    self.state = State::Ground;
+                    let index = match byte {
+                        b'n' => CharsetIndex::G2,
+                        _ => CharsetIndex::G3,
+                    };
+                    Some(Sequence::LockingShift(index))
+                }
+                b'~' | b'}' | b'|' => {
+                    self.state = State::Ground;
+                    let index = match byte {
+                        b'~' => CharsetIndex::G1,
+                        b'}' => CharsetIndex::G2,
+                        _ => CharsetIndex::G3,
+                    };
+                    Some(Sequence::LockingShiftRight(index))
+                }
+                b'D' => {
+                    self.state = State::Ground;
+                    Some(Sequence::Index)
+                }
+                b'N' | b'O' => {
+                    self.state = State::Ground;
+                    let index = match byte {
+                        b'N' => CharsetIndex::G2,
+                        _ => CharsetIndex::G3,
+                    };
+                    Some(Sequence::SingleShift(index))
+                }
+                _ => {
+                    self.state = State::Ground;
+                    Some(Sequence::Escape(byte))
+                }
+            },
+            State::Csi | State::CsiBang if byte == ESC => {
+                self.state = State::Escape;
+                None
+            }
+            State::Csi => {
+                self.state = if byte == b'!' {
+                    State::CsiBang
+                } else {
+                    State::Ground
+                };
+                Some(Sequence::Byte(byte))
+            }
+            State::CsiBang => {
+                self.state = State::Ground;
+                match byte {
+                    b'p' => Some(Sequence::SoftReset),
+                    _ => Some(Sequence::Byte(byte)),
+                }
+            }
+            State::EscapeHash => {
+                let line_attr = match byte {
+                    b'3' => LineAttr::DoubleHeightTop,
+                    b'4' => LineAttr::DoubleHeightBottom,
+                    b'5' => LineAttr::Normal,
+                    b'6' => LineAttr::DoubleWidth,
+                    _ => {
+                        self.state = State::Ground;
+                        return Some(Sequence::EscapeHash(byte));
+                    }
+                };
+                self.state = State::Ground;
+                Some(Sequence::LineAttr(line_attr))
+            }
+            State::Payload(kind) => {
+                if byte == ESC {
+                    self.state = State::PayloadEscape(kind);
+                } else {
+                    self.push_payload(byte);
+                }
+                None
+            }
+            State::PayloadEscape(kind) => match byte {
+                b'\\' => {
+                    self.state = State::Ground;
+                    let payload = core::mem::take(&mut self.payload);
+                    Some(match kind {
+                        PayloadKind::Dcs => Sequence::Dcs(payload),
+                        PayloadKind::Apc => Sequence::Apc(payload),
+                    })
+                }
+                ESC => {
+                    self.state = State::Payload(kind);
+                    self.push_payload(ESC);
+                    None
+                }
+                _ => {
+                    self.state = State::Payload(kind);
+                    self.push_payload(ESC);
+                    self.push_payload(byte);
+                    None
+                }
+            },
+        }
+    }
+
+    fn push_payload(&mut self, byte: u8) {
+        if self.payload.len() < MAX_PAYLOAD_SIZE {
+            self.payload.push(byte);
+        }
+    }
+}
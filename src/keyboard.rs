@@ -1,8 +1,10 @@
+use alloc::boxed::Box;
 use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 use pc_keyboard::layouts::Us104Key;
 use pc_keyboard::KeyCode::{self, *};
-use pc_keyboard::{DecodedKey, Keyboard};
-use pc_keyboard::{HandleControl, ScancodeSet1};
+use pc_keyboard::{DecodedKey, KeyEvent, Keyboard, KeyboardLayout, Modifiers};
+use pc_keyboard::{HandleControl, ScancodeSet, ScancodeSet1};
 
 pub enum KeyboardEvent {
     AnsiString(String),
@@ -11,13 +13,55 @@ pub enum KeyboardEvent {
     ScrollDown,
     ScrollPageUp,
     ScrollPageDown,
+    Copy,
     None,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortcutAction {
+    SetColorScheme(usize),
+    ScrollUp,
+    ScrollDown,
+    ScrollPageUp,
+    ScrollPageDown,
+    Copy,
+}
+
+struct Shortcut {
+    action: ShortcutAction,
+    key: KeyCode,
+    modifiers: Modifiers,
+}
+
+pub trait KeyboardLayoutBridge: Send {
+    fn add_byte(&mut self, scancode: u8) -> Option<KeyEvent>;
+    fn process_keyevent(&mut self, event: KeyEvent) -> Option<DecodedKey>;
+    fn get_modifiers(&self) -> &Modifiers;
+}
+
+impl<L, S> KeyboardLayoutBridge for Keyboard<L, S>
+where
+    L: KeyboardLayout + Send,
+    S: ScancodeSet + Send,
+{
+    fn add_byte(&mut self, scancode: u8) -> Option<KeyEvent> {
+        Keyboard::add_byte(self, scancode).ok().flatten()
+    }
+
+    fn process_keyevent(&mut self, event: KeyEvent) -> Option<DecodedKey> {
+        Keyboard::process_keyevent(self, event)
+    }
+
+    fn get_modifiers(&self) -> &Modifiers {
+        Keyboard::get_modifiers(self)
+    }
+}
+
 pub struct KeyboardManager {
     app_cursor_mode: bool,
     natural_scroll: bool,
-    keyboard: Keyboard<Us104Key, ScancodeSet1>,
+    shortcuts: Vec<Shortcut>,
+    keyboard: Box<dyn KeyboardLayoutBridge>,
 }
 
 impl Default for KeyboardManager {
@@ -25,11 +69,12 @@ impl Default for KeyboardManager {
         Self {
             app_cursor_mode: false,
             natural_scroll: true,
-            keyboard: Keyboard::new(
+            shortcuts: default_shortcuts(),
+            keyboard: Box::new(Keyboard::new(
                 ScancodeSet1::new(),
                 Us104Key,
                 HandleControl::MapLettersToUnicode,
-            ),
+            )),
         }
     }
 }
@@ -43,9 +88,31 @@ impl KeyboardManager {
         self.natural_scroll = mode;
     }
 
+    pub fn set_shortcut(&mut self, action: ShortcutAction, key: KeyCode, modifiers: Modifiers) {
+        self.remove_shortcut(action);
+        self.shortcuts.push(Shortcut {
+            action,
+            key,
+            modifiers,
+        });
+    }
+
+    pub fn set_layout(&mut self, layout: Box<dyn KeyboardLayoutBridge>) {
+        self.keyboard = layout;
+    }
+
+    pub fn remove_shortcut(&mut self, action: ShortcutAction) {
+        self.shortcuts.retain(|shortcut| shortcut.action != action);
+    }
+
     pub fn handle_keyboard(&mut self, scancode: u8) -> KeyboardEvent {
-        if let Some(key_event) = self.keyboard.add_byte(scancode).ok().flatten() {
+        if let Some(key_event) = self.keyboard.add_byte(scancode) {
+            let key = key_event.code;
             if let Some(decoded_key) = self.keyboard.process_keyevent(key_event) {
+                let modifiers = self.keyboard.get_modifiers();
+                if let Some(event) = self.shortcut_event(key, modifiers) {
+                    return event;
+                }
                 return self.key_to_ansi_string(decoded_key);
             }
         }
@@ -56,38 +123,11 @@ impl KeyboardManager {
 impl KeyboardManager {
     #[rustfmt::skip]
     fn key_to_ansi_string(&self, key: DecodedKey) -> KeyboardEvent {
-        let modifiers = self.keyboard.get_modifiers();
-
         match key {
             DecodedKey::Unicode(c) => {
                 KeyboardEvent::AnsiString(c.to_string())
             }
             DecodedKey::RawKey(key) => {
-                if modifiers.is_ctrl() && modifiers.is_shifted() {
-                    match (key, self.natural_scroll) {
-                        (ArrowUp, true) | (ArrowDown, false) => return KeyboardEvent::ScrollUp,
-                        (ArrowUp, false) | (ArrowDown, true) => return KeyboardEvent::ScrollDown,
-                        (PageUp, _) => return KeyboardEvent::ScrollPageUp,
-                        (PageDown, _) => return KeyboardEvent::ScrollPageDown,
-                        _ => {},
-                    };
-
-                    let palette_index = match key {
-                        KeyCode::F1 => Some(0),
-                        KeyCode::F2 => Some(1),
-                        KeyCode::F3 => Some(2),
-                        KeyCode::F4 => Some(3),
-                        KeyCode::F5 => Some(4),
-                        KeyCode::F6 => Some(5),
-                        KeyCode::F7 => Some(6),
-                        KeyCode::F8 => Some(7),
-                        _ => None,
-                    };
-                    if let Some(palette_index) = palette_index {
-                        return KeyboardEvent::SetColorScheme(palette_index as usize);
-                    }
-                }
-
                 let sequence = match key {
                     F1 => "\x1bOP",
                     F2 => "\x1bOQ",
@@ -119,4 +159,56 @@ impl KeyboardManager {
             }
         }
     }
+
+    fn shortcut_event(&self, key: KeyCode, modifiers: &Modifiers) -> Option<KeyboardEvent> {
+        let shortcut = self.shortcuts.iter().find(|shortcut| {
+            shortcut.key == key
+                && shortcut.modifiers.is_ctrl() == modifiers.is_ctrl()
+                && shortcut.modifiers.is_shifted() == modifiers.is_shifted()
+                && (!shortcut.modifiers.is_alt() || modifiers.is_alt())
+        })?;
+
+        Some(match (shortcut.action, self.natural_scroll) {
+            (ShortcutAction::SetColorScheme(index), _) => KeyboardEvent::SetColorScheme(index),
+            (ShortcutAction::ScrollUp, true) | (ShortcutAction::ScrollDown, false) => {
+                KeyboardEvent::ScrollUp
+            }
+            (ShortcutAction::ScrollUp, false) | (ShortcutAction::ScrollDown, true) => {
+                KeyboardEvent::ScrollDown
+            }
+            (ShortcutAction::ScrollPageUp, _) => KeyboardEvent::ScrollPageUp,
+            (ShortcutAction::ScrollPageDown, _) => KeyboardEvent::ScrollPageDown,
+            (ShortcutAction::Copy, _) => KeyboardEvent::Copy,
+        })
+    }
+}
+
+fn default_shortcuts() -> Vec<Shortcut> {
+    let modifiers = Modifiers {
+        lctrl: true,
+        lshift: true,
+        ..Default::default()
+    };
+
+    let palette_keys = [F1, F2, F3, F4, F5, F6, F7, F8];
+    let palette_shortcuts = palette_keys
+        .into_iter()
+        .enumerate()
+        .map(|(index, key)| (ShortcutAction::SetColorScheme(index), key));
+
+    [
+        (ShortcutAction::ScrollUp, ArrowUp),
+        (ShortcutAction::ScrollDown, ArrowDown),
+        (ShortcutAction::ScrollPageUp, PageUp),
+        (ShortcutAction::ScrollPageDown, PageDown),
+        (ShortcutAction::Copy, C),
+    ]
+    .into_iter()
+    .chain(palette_shortcuts)
+    .map(|(action, key)| Shortcut {
+        action,
+        key,
+        modifiers: modifiers.clone(),
+    })
+    .collect()
 }
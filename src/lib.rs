@@ -7,19 +7,37 @@ extern crate alloc;
 #[macro_use]
 mod log;
 
+mod accessible;
+mod base64;
 mod buffer;
 mod cell;
+mod clipboard;
 mod color;
 mod config;
 mod graphic;
+mod image;
+mod interceptor;
 mod keyboard;
+mod mouse;
 mod palette;
+mod pty;
 mod terminal;
 
+pub mod draw;
 pub mod font;
 
-pub use color::Rgb;
-pub use graphic::DrawTarget;
-pub use keyboard::KeyboardManager;
+pub use accessible::{AccessibleContent, AccessibleLine, AccessibleSpan};
+pub use buffer::HistoryStrategy;
+pub use cell::{Cell, Flags};
+pub use clipboard::{ClipboardHandler, NullClipboard};
+pub use color::{resolve_color, Color, ColorScheme, Rgb};
+pub use draw::{DrawTarget, HardwareCursor};
+pub use graphic::{ColorCacheStats, RenderStats};
+pub use image::{ImageFormat, ImageHandler, ImagePlacement};
+pub use keyboard::{KeyboardLayoutBridge, KeyboardManager, ShortcutAction};
+pub use mouse::{MouseButton, MouseInput};
 pub use palette::Palette;
+pub use pc_keyboard::{layouts, HandleControl, Keyboard, ScancodeSet1, ScancodeSet2};
+pub use pc_keyboard::{KeyCode, Modifiers};
+pub use pty::PtyWriter;
 pub use terminal::Terminal;
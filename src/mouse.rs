@@ -0,0 +1,85 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseButton {
+    Left,
+    Middle,
+    Right,
+}
+
+pub enum MouseInput {
+    Scroll(isize),
+    Moved(usize, usize),
+    Pressed(MouseButton),
+    Released(MouseButton),
+}
+
+pub enum MouseEvent {
+    Scroll(isize),
+    Pressed(MouseButton, usize, usize),
+    None,
+}
+
+pub struct MouseManager {
+    scroll_speed: usize,
+    scroll_inverted: bool,
+    click_to_move: bool,
+    middle_click_paste: bool,
+    position: (usize, usize),
+}
+
+impl Default for MouseManager {
+    fn default() -> Self {
+        Self {
+            scroll_speed: 1,
+            scroll_inverted: false,
+            click_to_move: false,
+            middle_click_paste: true,
+            position: (0, 0),
+        }
+    }
+}
+
+impl MouseManager {
+    pub fn set_scroll_speed(&mut self, speed: usize) {
+        self.scroll_speed = speed;
+    }
+
+    pub fn set_scroll_inverted(&mut self, inverted: bool) {
+        self.scroll_inverted = inverted;
+    }
+
+    pub fn set_click_to_move(&mut self, mode: bool) {
+        self.click_to_move = mode;
+    }
+
+    pub fn click_to_move(&self) -> bool {
+        self.click_to_move
+    }
+
+    pub fn set_middle_click_paste(&mut self, mode: bool) {
+        self.middle_click_paste = mode;
+    }
+
+    pub fn middle_click_paste(&self) -> bool {
+        self.middle_click_paste
+    }
+
+    pub fn handle_mouse(&mut self, input: MouseInput) -> MouseEvent {
+        match input {
+            MouseInput::Scroll(lines) => {
+                let lines = lines * self.scroll_speed as isize;
+                match if self.scroll_inverted { -lines } else { lines } {
+                    0 => MouseEvent::None,
+                    lines => MouseEvent::Scroll(lines),
+                }
+            }
+            MouseInput::Moved(x, y) => {
+                self.position = (x, y);
+                MouseEvent::None
+            }
+            MouseInput::Pressed(button) => {
+                MouseEvent::Pressed(button, self.position.0, self.position.1)
+            }
+            MouseInput::Released(_) => MouseEvent::None,
+        }
+    }
+}
@@ -10,20 +10,35 @@ pub struct Palette {
 }
 
 impl Palette {
-    fn build(pair: (&str, &str), ansi_colors: [&str; 16]) -> Self {
+    pub const fn const_new(foreground: u32, background: u32, ansi_colors: [u32; 16]) -> Self {
+        let mut colors = [(0, 0, 0); 16];
+        let mut i = 0;
+        while i < 16 {
+            colors[i] = Self::u32_to_rgb(ansi_colors[i]);
+            i += 1;
+        }
+
         Self {
-            foreground: Self::hex_to_rgb(pair.0),
-            background: Self::hex_to_rgb(pair.1),
-            ansi_colors: ansi_colors.map(Self::hex_to_rgb),
+            foreground: Self::u32_to_rgb(foreground),
+            background: Self::u32_to_rgb(background),
+            ansi_colors: colors,
         }
     }
 
-    fn hex_to_rgb(hex: &str) -> Rgb {
-        let hex = hex.trim_start_matches('#');
-        let r = u8::from_str_radix(&hex[0..2], 16).unwrap_or(0);
-        let g = u8::from_str_radix(&hex[2..4], 16).unwrap_or(0);
-        let b = u8::from_str_radix(&hex[4..6], 16).unwrap_or(0);
-        (r, g, b)
+    fn build(pair: (&str, &str), ansi_colors: [&str; 16]) -> Self {
+        Self::const_new(
+            Self::hex_to_u32(pair.0),
+            Self::hex_to_u32(pair.1),
+            ansi_colors.map(Self::hex_to_u32),
+        )
+    }
+
+    const fn u32_to_rgb(color: u32) -> Rgb {
+        ((color >> 16) as u8, (color >> 8) as u8, color as u8)
+    }
+
+    fn hex_to_u32(hex: &str) -> u32 {
+        u32::from_str_radix(hex.trim_start_matches('#'), 16).unwrap_or(0)
     }
 }
 
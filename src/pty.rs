@@ -0,0 +1,11 @@
+pub trait PtyWriter: Send {
+    fn write(&mut self, data: &str);
+
+    fn flush(&mut self) {}
+}
+
+impl<F: FnMut(&str) + Send> PtyWriter for F {
+    fn write(&mut self, data: &str) {
+        self(data)
+    }
+}
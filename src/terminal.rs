@@ -1,24 +1,36 @@
 use alloc::boxed::Box;
 use alloc::string::String;
-use core::mem::swap;
+use alloc::vec::Vec;
+use core::mem::{size_of, swap};
 use core::sync::atomic::Ordering;
 use core::time::Duration;
-use core::{cmp::min, fmt};
+use core::{cmp::min, fmt, ops::Range};
 
-use vte::ansi::{Attr, Color as AnsiColor, NamedMode, Rgb};
+use pc_keyboard::{KeyCode, Modifiers};
+use vte::ansi::{Attr, Color as AnsiColor, NamedMode, Rgb as AnsiRgb};
 use vte::ansi::{CharsetIndex, StandardCharset, TabulationClearMode};
 use vte::ansi::{ClearMode, CursorShape, Processor, Timeout};
 use vte::ansi::{CursorStyle, Hyperlink, KeyboardModes};
 use vte::ansi::{Handler, LineClearMode, Mode, NamedPrivateMode, PrivateMode};
 
-use crate::buffer::TerminalBuffer;
-use crate::cell::{Cell, Flags};
-use crate::color::{Color, ColorScheme};
+use crate::accessible::{AccessibleContent, AccessibleLine};
+use crate::base64;
+use crate::buffer::{HistoryStrategy, TerminalBuffer};
+use crate::cell::{Cell, Flags, LineAttr};
+use crate::clipboard::ClipboardHandler;
+use crate::color::{Color, ColorScheme, Rgb};
 use crate::config::CONFIG;
-use crate::font::FontManager;
-use crate::graphic::{DrawTarget, Graphic};
-use crate::keyboard::{KeyboardEvent, KeyboardManager};
+use crate::draw::{DrawTarget, HardwareCursor};
+use crate::font::{FontManager, SharedFontManager};
+use crate::graphic::{ColorCache, Graphic, RenderStats};
+use crate::image::{ImageHandler, ImagePlacement, KittyCommand, MAX_IMAGE_SIZE};
+use crate::interceptor::{Interceptor, Sequence};
+use crate::keyboard::{KeyboardEvent, KeyboardLayoutBridge, KeyboardManager, ShortcutAction};
+use crate::mouse::{MouseButton, MouseEvent, MouseInput, MouseManager};
 use crate::palette::Palette;
+use crate::pty::PtyWriter;
+
+const TITLE_STACK_SIZE: usize = 16;
 
 #[derive(Default)]
 pub struct DummySyncHandler;
@@ -31,6 +43,7 @@ impl Timeout for DummySyncHandler {
 }
 
 bitflags::bitflags! {
+    #[derive(Debug, Clone, Copy)]
     pub struct TerminalMode: u32 {
         const SHOW_CURSOR = 1 << 0;
         const APP_CURSOR = 1 << 1;
@@ -51,6 +64,8 @@ bitflags::bitflags! {
         const ALTERNATE_SCROLL = 1 << 16;
         const VI = 1 << 17;
         const URGENCY_HINTS = 1 << 18;
+        const VT52 = 1 << 19;
+        const REVERSE_WRAP = 1 << 20;
         const ANY = u32::MAX;
     }
 }
@@ -68,20 +83,47 @@ struct Cursor {
     shape: CursorShape,
 }
 
+#[derive(Debug, Default, Clone, Copy)]
+struct SavedCursorState {
+    cursor: Cursor,
+    mode: TerminalMode,
+    attribute_template: Cell,
+    charsets: [StandardCharset; 4],
+    active_charset: CharsetIndex,
+}
+
 pub struct Terminal<D: DrawTarget> {
     performer: Processor<DummySyncHandler>,
+    interceptor: Interceptor,
     inner: TerminalInner<D>,
 }
 
 pub struct TerminalInner<D: DrawTarget> {
     cursor: Cursor,
-    saved_cursor: Cursor,
+    saved_cursor: SavedCursorState,
+    alt_saved_state: SavedCursorState,
     alt_cursor: Cursor,
     mode: TerminalMode,
     attribute_template: Cell,
+    default_cell: Option<Cell>,
     buffer: TerminalBuffer<D>,
     keyboard: KeyboardManager,
+    mouse: MouseManager,
     scroll_region: (usize, usize),
+    saved_scroll_region: (usize, usize),
+    preedit: String,
+    preedit_cursor: Option<(usize, usize)>,
+    preedit_saved: Vec<(usize, usize, Cell)>,
+    pty_buffer: String,
+    charsets: [StandardCharset; 4],
+    active_charset: CharsetIndex,
+    pending_single_shift: Option<CharsetIndex>,
+    synced_output: bool,
+    cursor_blink_requested: bool,
+    image_transfer: Option<(KittyCommand, Vec<u8>)>,
+    secondary_da: String,
+    title: String,
+    title_stack: Vec<String>,
 }
 
 impl<D: DrawTarget> Terminal<D> {
@@ -91,19 +133,49 @@ impl<D: DrawTarget> Terminal<D> {
 
         Self {
             performer: Processor::new(),
+            interceptor: Interceptor::default(),
             inner: TerminalInner {
                 cursor: Cursor::default(),
-                saved_cursor: Cursor::default(),
+                saved_cursor: SavedCursorState::default(),
+                alt_saved_state: SavedCursorState::default(),
                 alt_cursor: Cursor::default(),
                 mode: TerminalMode::default(),
                 attribute_template: Cell::default(),
+                default_cell: None,
                 buffer: TerminalBuffer::new(graphic),
                 keyboard: KeyboardManager::default(),
+                mouse: MouseManager::default(),
                 scroll_region: (0, 0),
+                saved_scroll_region: (0, 0),
+                preedit: String::new(),
+                preedit_cursor: None,
+                preedit_saved: Vec::new(),
+                pty_buffer: String::new(),
+                charsets: [StandardCharset::Ascii; 4],
+                active_charset: CharsetIndex::G0,
+                pending_single_shift: None,
+                synced_output: false,
+                cursor_blink_requested: false,
+                image_transfer: None,
+                secondary_da: secondary_da(0, env!("CARGO_PKG_VERSION"), 1),
+                title: String::new(),
+                title_stack: Vec::new(),
             },
         }
     }
 
+    pub fn title(&self) -> &str {
+        &self.inner.title
+    }
+
+    pub fn is_alt_screen_active(&self) -> bool {
+        self.inner.mode.contains(TerminalMode::ALT_SCREEN)
+    }
+
+    pub fn cursor_blink_requested(&self) -> bool {
+        self.inner.cursor_blink_requested
+    }
+
     pub fn rows(&self) -> usize {
         self.inner.buffer.height()
     }
@@ -112,21 +184,195 @@ impl<D: DrawTarget> Terminal<D> {
         self.inner.buffer.width()
     }
 
+    pub fn iter_rows(&self) -> impl Iterator<Item = &[Cell]> {
+        self.inner.buffer.rows()
+    }
+
     pub fn flush(&mut self) {
+        self.inner.buffer.detect_urls();
         self.inner.buffer.flush();
+        self.inner.update_hardware_cursor();
+    }
+
+    pub fn dirty_rows(&self) -> impl Iterator<Item = usize> + '_ {
+        self.inner.buffer.dirty_rows()
+    }
+
+    pub fn draw_row(&mut self, row: usize) {
+        self.inner.buffer.detect_urls();
+        self.inner.buffer.draw_partial_row(row);
+    }
+
+    pub fn finish_partial_flush(&mut self) {
+        self.inner.buffer.finish_partial_flush();
+        self.inner.update_hardware_cursor();
+    }
+
+    fn auto_flush(&mut self) {
+        if CONFIG.auto_flush.load(Ordering::Relaxed) && !self.inner.synced_output {
+            self.flush();
+        }
+    }
+
+    pub fn accessible_text(&self) -> AccessibleContent {
+        let lines = self.inner.buffer.rows().map(AccessibleLine::from_cells);
+        AccessibleContent {
+            lines: lines.collect(),
+        }
+    }
+
+    pub fn render_stats(&self) -> RenderStats {
+        self.inner.buffer.render_stats()
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.inner.buffer.reset_stats();
     }
 
     pub fn process(&mut self, bstr: &[u8]) {
-        self.inner.cursor_handler(false);
-        for &byte in bstr {
-            self.performer.advance(&mut self.inner, byte);
+        if self.inner.buffer.is_empty() {
+            log!("Ignoring {} bytes for an empty screen", bstr.len());
+            return;
         }
+
+        self.inner.restore_preedit();
+        self.inner.cursor_handler(false);
+        self.advance(bstr);
+        self.inner.flush_pty();
         if self.inner.mode.contains(TerminalMode::SHOW_CURSOR) {
             self.inner.cursor_handler(true);
         }
-        if CONFIG.auto_flush.load(Ordering::Relaxed) {
-            self.flush();
+        self.inner.draw_preedit();
+        self.auto_flush();
+    }
+
+    pub fn process_str(&mut self, text: &str) {
+        self.process(text.as_bytes());
+    }
+
+    pub fn write_bstr(&mut self, bstr: &[u8]) {
+        self.process(bstr);
+    }
+
+    fn advance(&mut self, bstr: &[u8]) {
+        for &byte in bstr {
+            let vt52 = self.inner.mode.contains(TerminalMode::VT52);
+            self.interceptor.set_vt52(vt52);
+
+            match self.interceptor.advance(byte) {
+                Some(Sequence::Byte(byte)) => self.performer.advance(&mut self.inner, byte),
+                Some(Sequence::Escape(byte)) => {
+                    self.performer.advance(&mut self.inner, 0x1b);
+                    self.performer.advance(&mut self.inner, byte);
+                }
+                Some(Sequence::EscapeHash(byte)) => {
+                    self.performer.advance(&mut self.inner, 0x1b);
+                    self.performer.advance(&mut self.inner, b'#');
+                    self.performer.advance(&mut self.inner, byte);
+                }
+                Some(Sequence::Dcs(data)) => self.handle_dcs(&data),
+                Some(Sequence::Apc(data)) => self.inner.handle_apc(&data),
+                Some(Sequence::Vt52(byte)) => self.inner.vt52_dispatch(byte),
+                Some(Sequence::Vt52Goto(row, column)) => {
+                    log!("VT52 goto: {}, {}", row, column);
+                    self.inner.goto(row as i32, column);
+                }
+                Some(Sequence::Index) => self.inner.index(),
+                Some(Sequence::SoftReset) => {
+                    self.performer.advance(&mut self.inner, b'p');
+                    self.inner.soft_reset();
+                }
+                Some(Sequence::LineAttr(line_attr)) => {
+                    log!("Set line attribute: {:?}", line_attr);
+                    let row = self.inner.cursor.row;
+                    self.inner.buffer.set_line_attr(row, line_attr);
+                }
+                Some(Sequence::LockingShift(index)) => self.inner.set_active_charset(index),
+                Some(Sequence::LockingShiftRight(index)) => {
+                    log!("Unhandled locking shift right: {:?}", index);
+                }
+                Some(Sequence::SingleShift(index)) => {
+                    log!("Single shift: {:?}", index);
+                    self.inner.pending_single_shift = Some(index);
+                }
+                None => {}
+            }
+        }
+    }
+
+    fn handle_dcs(&mut self, data: &[u8]) {
+        if let Some(inner) = data.strip_prefix(b"tmux;") {
+            if CONFIG.tmux_passthrough.load(Ordering::Relaxed) {
+                log!("Tmux passthrough: {} bytes", inner.len());
+                self.advance(inner);
+                return;
+            }
+        }
+        if let Some(handler) = *CONFIG.dcs_handler.lock() {
+            handler(data);
+            return;
+        }
+        log!("Unhandled DCS: {:?}", data);
+    }
+
+    pub fn handle_ime_preedit(&mut self, text: &str, cursor: Option<(usize, usize)>) {
+        self.inner.restore_preedit();
+        self.inner.preedit = text.into();
+        self.inner.preedit_cursor = cursor;
+        self.inner.draw_preedit();
+        self.auto_flush();
+    }
+
+    pub fn write_str_at(&mut self, row: usize, col: usize, text: &str, attr: &Cell) {
+        if row >= self.rows() {
+            return;
+        }
+
+        let mut column = col;
+        for content in text.chars() {
+            let cell = attr.set_content(content);
+            let width = if cell.wide { 2 } else { 1 };
+
+            if column + width > self.columns() {
+                break;
+            }
+
+            self.inner.buffer.write(row, column, cell);
+            if cell.wide {
+                self.inner
+                    .buffer
+                    .write(row, column + 1, cell.set_placeholder());
+            }
+            column += width;
+        }
+
+        self.auto_flush();
+    }
+
+    pub fn clear_region(&mut self, rows: Range<usize>, cols: Range<usize>) {
+        let template = self.inner.attribute_template.clear();
+        self.fill_region(rows, cols, template);
+    }
+
+    pub fn fill_region(&mut self, rows: Range<usize>, cols: Range<usize>, cell: Cell) {
+        let rows = rows.start..min(rows.end, self.rows());
+        let cols = cols.start..min(cols.end, self.columns());
+
+        for row in rows {
+            for column in cols.clone() {
+                self.inner.buffer.write(row, column, cell);
+            }
         }
+
+        self.auto_flush();
+    }
+
+    pub fn inject_char(&mut self, content: char) {
+        if !self.inner.buffer.is_latest() {
+            self.inner.buffer.back_to_latest();
+        }
+        self.inner.pty_write(content.encode_utf8(&mut [0; 4]));
+        self.inner.flush_pty();
     }
 
     pub fn handle_keyboard(&mut self, scancode: u8) -> Option<String> {
@@ -145,10 +391,103 @@ impl<D: DrawTarget> Terminal<D> {
             KeyboardEvent::ScrollDown => self.inner.scroll_history_down(1),
             KeyboardEvent::ScrollPageUp => self.inner.scroll_history_up(self.rows()),
             KeyboardEvent::ScrollPageDown => self.inner.scroll_history_down(self.rows()),
+            KeyboardEvent::Copy => self.copy_screen(),
             _ => {}
         }
         None
     }
+
+    fn copy_screen(&mut self) {
+        let mut text = String::new();
+        for row in self.inner.buffer.rows() {
+            let line: String = row
+                .iter()
+                .filter(|cell| !cell.placeholder)
+                .map(|cell| cell.content)
+                .collect();
+            text.push_str(line.trim_end());
+            text.push('\n');
+        }
+
+        let text = text.trim_end_matches('\n').into();
+        if let Some(clipboard) = CONFIG.clipboard.lock().as_mut() {
+            clipboard.set_text(text);
+        }
+    }
+
+    pub fn handle_mouse(&mut self, input: MouseInput) -> Option<String> {
+        let sequence = match self.inner.mouse.handle_mouse(input) {
+            MouseEvent::Scroll(lines) => self.handle_mouse_scroll(lines),
+            MouseEvent::Pressed(button, x, y) => self.handle_mouse_press(button, x, y),
+            MouseEvent::None => None,
+        }?;
+        self.inner.pty_write(&sequence);
+        self.inner.flush_pty();
+        Some(sequence)
+    }
+
+    pub fn scroll_history_by(&mut self, count: isize) -> isize {
+        log!("Scroll history by: {}", count);
+        let buffer = &mut self.inner.buffer;
+        let scrolled = if count > 0 {
+            buffer.scroll_history(count as usize, false) as isize
+        } else {
+            -(buffer.scroll_history(count.unsigned_abs(), true) as isize)
+        };
+        self.auto_flush();
+        scrolled
+    }
+
+    fn handle_mouse_scroll(&mut self, lines: isize) -> Option<String> {
+        if self.inner.mode.contains(TerminalMode::ALT_SCREEN) {
+            let app_cursor = self.inner.mode.contains(TerminalMode::APP_CURSOR);
+            let sequence = match (lines > 0, app_cursor) {
+                (true, true) => "\x1bOA",
+                (true, false) => "\x1b[A",
+                (false, true) => "\x1bOB",
+                (false, false) => "\x1b[B",
+            };
+            return Some(sequence.repeat(lines.unsigned_abs()));
+        }
+
+        if lines > 0 {
+            self.inner.scroll_history_down(lines as usize);
+        } else {
+            self.inner.scroll_history_up(lines.unsigned_abs());
+        }
+        None
+    }
+
+    fn handle_mouse_press(&mut self, button: MouseButton, x: usize, y: usize) -> Option<String> {
+        let mouse_report = TerminalMode::MOUSE_REPORT_CLICK
+            | TerminalMode::MOUSE_DRAG
+            | TerminalMode::MOUSE_MOTION
+            | TerminalMode::SGR_MOUSE;
+
+        if button == MouseButton::Left {
+            if let Some(handler) = *CONFIG.url_handler.lock() {
+                let (row, column) = self.inner.pixel_to_cell(x, y)?;
+                if let Some(url) = self.inner.buffer.url_at(row, column) {
+                    handler(&url);
+                    return None;
+                }
+            }
+        }
+
+        if button == MouseButton::Left
+            && self.inner.mouse.click_to_move()
+            && !self.inner.mode.intersects(mouse_report)
+        {
+            let (row, column) = self.inner.pixel_to_cell(x, y)?;
+            return Some(format!("\x1b[{};{}H", row + 1, column + 1));
+        }
+
+        if button == MouseButton::Middle && self.inner.mouse.middle_click_paste() {
+            let text = CONFIG.clipboard.lock().as_mut()?.get_primary_selection()?;
+            return Some(self.inner.paste_text(&text));
+        }
+        None
+    }
 }
 
 impl<D: DrawTarget> Terminal<D> {
@@ -164,10 +503,121 @@ impl<D: DrawTarget> Terminal<D> {
         *CONFIG.bell_handler.lock() = handler;
     }
 
+    pub fn set_scroll_handler(&mut self, handler: Option<fn(usize, usize)>) {
+        *CONFIG.scroll_handler.lock() = handler;
+    }
+
+    pub fn set_url_handler(&mut self, handler: Option<fn(&str)>) {
+        *CONFIG.url_handler.lock() = handler;
+    }
+
+    pub fn set_title_handler(&mut self, handler: Option<fn(&str)>) {
+        *CONFIG.title_handler.lock() = handler;
+    }
+
+    pub fn set_dcs_handler(&mut self, handler: Option<fn(&[u8])>) {
+        *CONFIG.dcs_handler.lock() = handler;
+    }
+
+    pub fn set_clock(&mut self, clock: Option<fn() -> Duration>) {
+        *CONFIG.clock.lock() = clock;
+    }
+
+    pub fn set_hardware_cursor(&mut self, cursor: Option<Box<dyn HardwareCursor>>) {
+        self.inner.cursor_handler(false);
+        if let Some(old_cursor) = CONFIG.hardware_cursor.lock().as_mut() {
+            old_cursor.set_cursor_visible(false);
+        }
+        *CONFIG.hardware_cursor.lock() = cursor;
+    }
+
+    pub fn set_pty_writer(&mut self, pty_writer: Box<dyn PtyWriter>) {
+        *CONFIG.pty_writer.lock() = Some(pty_writer);
+    }
+
+    pub fn set_secondary_da(&mut self, type_id: u16, version: &str, options: u8) {
+        self.inner.secondary_da = secondary_da(type_id, version, options);
+    }
+
+    pub fn set_image_handler(&mut self, handler: Box<dyn ImageHandler>) {
+        *CONFIG.image_handler.lock() = Some(handler);
+    }
+
+    pub fn set_clipboard(&mut self, clipboard: Box<dyn ClipboardHandler>) {
+        *CONFIG.clipboard.lock() = Some(clipboard);
+    }
+
+    pub fn set_scrollbar_width(&mut self, pixels: usize) {
+        self.inner.buffer.set_scrollbar_width(pixels);
+        self.update_grid_size();
+    }
+
+    fn update_grid_size(&mut self) {
+        let Some(font_size) = CONFIG.with_font_manager(|f| f.size()) else {
+            return;
+        };
+        self.inner.restore_preedit();
+        self.inner.cursor_handler(false);
+        self.inner.update_grid_size(font_size);
+        if self.inner.mode.contains(TerminalMode::SHOW_CURSOR) {
+            self.inner.cursor_handler(true);
+        }
+        self.inner.draw_preedit();
+        self.auto_flush();
+    }
+
+    pub fn resize(&mut self) {
+        self.update_grid_size();
+    }
+
+    pub fn set_scrollbar_colors(&mut self, track: Rgb, thumb: Rgb) {
+        self.inner.buffer.set_scrollbar_colors(track, thumb);
+    }
+
     pub fn set_history_size(&mut self, size: usize) {
         self.inner.buffer.resize_history(size);
     }
 
+    pub fn set_history_strategy(&mut self, strategy: HistoryStrategy) {
+        self.inner.buffer.set_history_strategy(strategy);
+    }
+
+    pub fn set_color_cache_size(&mut self, size: usize) {
+        self.inner.buffer.set_color_cache_size(size);
+    }
+
+    pub fn set_color_cache_memory(&mut self, bytes: usize) {
+        self.set_color_cache_size(bytes / size_of::<ColorCache>());
+    }
+
+    pub fn set_scroll_speed(&mut self, speed: usize) {
+        self.inner.mouse.set_scroll_speed(speed);
+    }
+
+    pub fn set_scroll_direction(&mut self, inverted: bool) {
+        self.inner.mouse.set_scroll_inverted(inverted);
+    }
+
+    pub fn set_click_to_move(&mut self, mode: bool) {
+        self.inner.mouse.set_click_to_move(mode);
+    }
+
+    pub fn set_middle_click_paste(&mut self, mode: bool) {
+        self.inner.mouse.set_middle_click_paste(mode);
+    }
+
+    pub fn set_shortcut(&mut self, action: ShortcutAction, key: KeyCode, modifiers: Modifiers) {
+        self.inner.keyboard.set_shortcut(action, key, modifiers);
+    }
+
+    pub fn remove_shortcut(&mut self, action: ShortcutAction) {
+        self.inner.keyboard.remove_shortcut(action);
+    }
+
+    pub fn set_keyboard_layout_dynamic(&mut self, layout: Box<dyn KeyboardLayoutBridge>) {
+        self.inner.keyboard.set_layout(layout);
+    }
+
     pub fn set_natural_scroll(&mut self, mode: bool) {
         self.inner.keyboard.set_natural_scroll(mode);
     }
@@ -176,38 +626,70 @@ impl<D: DrawTarget> Terminal<D> {
         CONFIG.auto_crnl.store(auto_crnl, Ordering::Relaxed);
     }
 
+    pub fn set_tmux_passthrough(&mut self, tmux_passthrough: bool) {
+        CONFIG
+            .tmux_passthrough
+            .store(tmux_passthrough, Ordering::Relaxed);
+    }
+
     pub fn set_font_manager(&mut self, font_manager: Box<dyn FontManager>) {
-        let (font_width, font_height) = font_manager.size();
-        self.inner.buffer.update_size(font_width, font_height);
-        self.inner.scroll_region = (0, self.inner.buffer.height() - 1);
-        self.inner.reset_state();
+        self.inner.update_font_size(font_manager.size());
+        *CONFIG.shared_font_manager.lock() = None;
         *CONFIG.font_manager.lock() = Some(font_manager);
     }
 
+    pub fn set_font_manager_shared(&mut self, font_manager: SharedFontManager) {
+        self.inner.update_font_size(font_manager.lock().size());
+        *CONFIG.font_manager.lock() = None;
+        *CONFIG.shared_font_manager.lock() = Some(font_manager);
+    }
+
+    pub fn set_default_cell(&mut self, template: Cell) {
+        self.inner.default_cell = Some(template);
+        self.inner.attribute_template = template;
+    }
+
+    pub fn pre_warm(&mut self, chars: &[char]) {
+        CONFIG.with_font_manager(|font_manager| font_manager.pre_warm(chars));
+    }
+
+    pub fn color_scheme(&self) -> ColorScheme {
+        CONFIG.color_scheme.lock().clone()
+    }
+
     pub fn set_color_scheme(&mut self, palette_index: usize) {
         *CONFIG.color_scheme.lock() = ColorScheme::new(palette_index);
-        self.inner.attribute_template = Cell::default();
-        self.inner.buffer.full_flush();
+        self.inner.attribute_template = self.inner.default_cell();
+        self.inner.buffer.reset_colors();
+        self.auto_flush();
     }
 
     pub fn set_custom_color_scheme(&mut self, palette: Palette) {
         *CONFIG.color_scheme.lock() = ColorScheme::from_palette(&palette);
-        self.inner.attribute_template = Cell::default();
-        self.inner.buffer.full_flush();
+        self.inner.attribute_template = self.inner.default_cell();
+        self.inner.buffer.reset_colors();
+        self.auto_flush();
     }
 }
 
 impl<D: DrawTarget> fmt::Write for Terminal<D> {
     fn write_str(&mut self, s: &str) -> fmt::Result {
-        self.process(s.as_bytes());
+        self.process_str(s);
         Ok(())
     }
 }
 
 impl<D: DrawTarget> TerminalInner<D> {
     fn cursor_handler(&mut self, enable: bool) {
+        if self.buffer.is_empty() || (enable && CONFIG.hardware_cursor.lock().is_some()) {
+            return;
+        }
+
         let row = self.cursor.row % self.buffer.height();
-        let column = self.cursor.column % self.buffer.width();
+        let mut column = self.cursor.column % self.buffer.width();
+        if column > 0 && self.buffer.read(row, column).placeholder {
+            column -= 1;
+        }
 
         let mut origin_cell = self.buffer.read(row, column);
 
@@ -228,6 +710,264 @@ impl<D: DrawTarget> TerminalInner<D> {
         self.buffer.write(row, column, origin_cell);
     }
 
+    fn update_font_size(&mut self, (font_width, font_height): (usize, usize)) {
+        if self.mode.contains(TerminalMode::ALT_SCREEN) {
+            self.swap_alt_screen();
+        }
+        let history_offset = self.buffer.history_offset();
+        self.buffer.back_to_latest();
+        self.buffer.update_size(font_width, font_height, 0);
+        self.reset_screen();
+        self.buffer.scroll_history(history_offset, false);
+    }
+
+    fn update_grid_size(&mut self, (font_width, font_height): (usize, usize)) {
+        let old_height = self.buffer.height();
+        let history_offset = self.buffer.history_offset();
+        self.buffer.back_to_latest();
+        let removed = self
+            .buffer
+            .update_size(font_width, font_height, self.cursor.row);
+        self.buffer.scroll_history(history_offset, false);
+
+        let (width, height) = (self.buffer.width(), self.buffer.height());
+        for cursor in [
+            &mut self.cursor,
+            &mut self.alt_cursor,
+            &mut self.saved_cursor.cursor,
+        ] {
+            cursor.row = min(cursor.row.saturating_sub(removed), height.saturating_sub(1));
+            cursor.column = min(cursor.column, width);
+        }
+        if height != old_height {
+            self.scroll_region = (0, height.saturating_sub(1));
+            self.saved_scroll_region = self.scroll_region;
+        }
+    }
+
+    fn reset_screen(&mut self) {
+        if self.mode.contains(TerminalMode::ALT_SCREEN) {
+            self.swap_alt_screen();
+        }
+        self.buffer.clear(self.default_cell());
+        self.cursor = Cursor::default();
+        self.mode = TerminalMode::default();
+        self.attribute_template = self.default_cell();
+        self.scroll_region = (0, self.buffer.height().saturating_sub(1));
+        self.charsets = [StandardCharset::Ascii; 4];
+        self.active_charset = CharsetIndex::G0;
+        self.pending_single_shift = None;
+        self.synced_output = false;
+        self.image_transfer = None;
+        self.saved_cursor = self.saved_state();
+    }
+
+    fn pty_write(&mut self, data: &str) {
+        if data.is_empty() {
+            return;
+        }
+        self.pty_buffer.push_str(data);
+    }
+
+    fn pty_write_bytes(&mut self, data: &[u8]) {
+        match core::str::from_utf8(data) {
+            Ok(data) => self.pty_write(data),
+            Err(_) => log!("Invalid pty response: {:?}", data),
+        }
+    }
+
+    fn flush_pty(&mut self) {
+        if self.pty_buffer.is_empty() {
+            return;
+        }
+        if let Some(pty_writer) = CONFIG.pty_writer.lock().as_mut() {
+            pty_writer.write(&self.pty_buffer);
+            pty_writer.flush();
+        }
+        self.pty_buffer.clear();
+    }
+
+    fn handle_apc(&mut self, data: &[u8]) {
+        let Some(data) = data.strip_prefix(b"G") else {
+            log!("Unhandled APC: {:?}", data);
+            return;
+        };
+        if CONFIG.image_handler.lock().is_none() {
+            log!("Kitty graphics without image handler");
+            return;
+        }
+
+        let (control, payload) = match data.iter().position(|&byte| byte == b';') {
+            Some(index) => (&data[..index], &data[index + 1..]),
+            None => (data, &[][..]),
+        };
+        let chunk = KittyCommand::parse(control);
+        let (command, mut image) = self
+            .image_transfer
+            .take()
+            .unwrap_or_else(|| (chunk, Vec::new()));
+
+        if !base64::decode(payload, &mut image) || image.len() > MAX_IMAGE_SIZE {
+            self.kitty_reply(&command, "EINVAL:invalid image data");
+            return;
+        }
+        if chunk.more {
+            self.image_transfer = Some((command, image));
+            return;
+        }
+
+        match command.action {
+            b'q' => self.kitty_reply(&command, "OK"),
+            b'T' => self.render_image(&command, &image),
+            action => log!("Unhandled kitty graphics action: {}", action as char),
+        }
+    }
+
+    fn render_image(&mut self, command: &KittyCommand, image: &[u8]) {
+        let Some((font_width, font_height)) = CONFIG.with_font_manager(|f| f.size()) else {
+            return;
+        };
+
+        let placement = ImagePlacement {
+            x: self.cursor.column * font_width,
+            y: self.cursor.row * font_height,
+            width: command.width,
+            height: command.height,
+            columns: command.columns,
+            rows: command.rows,
+            format: command.format,
+        };
+        if let Some(handler) = CONFIG.image_handler.lock().as_mut() {
+            handler.render_image(command.id, image, placement, self.buffer.draw_target());
+        }
+        self.kitty_reply(command, "OK");
+
+        if command.keep_cursor {
+            return;
+        }
+        let rows = match command.rows {
+            0 => command.height.div_ceil(font_height),
+            rows => rows,
+        };
+        let columns = match command.columns {
+            0 => command.width.div_ceil(font_width),
+            columns => columns,
+        };
+        let column = self.cursor.column + columns;
+        for _ in 1..rows {
+            self.linefeed();
+        }
+        self.cursor.column = min(column, self.buffer.width() - 1);
+    }
+
+    fn kitty_reply(&mut self, command: &KittyCommand, message: &str) {
+        let quiet = if message == "OK" { 1 } else { 2 };
+        if command.id == 0 || command.quiet >= quiet {
+            return;
+        }
+        self.pty_write(&format!("\x1b_Gi={};{}\x1b\\", command.id, message));
+    }
+
+    fn paste_text(&self, text: &str) -> String {
+        if self.mode.contains(TerminalMode::BRACKETED_PASTE) {
+            format!("\x1b[200~{}\x1b[201~", text)
+        } else {
+            text.into()
+        }
+    }
+
+    fn pixel_to_cell(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        let (font_width, font_height) = CONFIG.with_font_manager(|f| f.size())?;
+        if font_width == 0 || font_height == 0 || self.buffer.is_empty() {
+            return None;
+        }
+
+        let row = min(y / font_height, self.buffer.height() - 1);
+        let column = min(x / font_width, self.buffer.width() - 1);
+        Some((row, column))
+    }
+
+    fn restore_preedit(&mut self) {
+        for (row, column, cell) in self.preedit_saved.drain(..).rev() {
+            if row < self.buffer.height() && column < self.buffer.width() {
+                self.buffer.write(row, column, cell);
+            }
+        }
+    }
+
+    fn draw_preedit(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+
+        let row = min(self.cursor.row, self.buffer.height() - 1);
+        let mut column = min(self.cursor.column, self.buffer.width() - 1);
+
+        for (index, content) in self.preedit.char_indices() {
+            let mut template = self.attribute_template.set_content(content);
+            template.flags.insert(Flags::UNDERLINE);
+
+            match self.preedit_cursor {
+                Some((start, end)) if start == end && index == start => {
+                    template.flags.insert(Flags::CURSOR_BEAM);
+                }
+                Some((start, end)) if (start..end).contains(&index) => {
+                    template.flags.insert(Flags::INVERSE);
+                }
+                _ => {}
+            }
+
+            let width = if template.wide { 2 } else { 1 };
+            if column + width > self.buffer.width() {
+                break;
+            }
+
+            self.preedit_saved
+                .push((row, column, self.buffer.read(row, column)));
+            self.buffer.write(row, column, template);
+
+            if template.wide {
+                self.preedit_saved
+                    .push((row, column + 1, self.buffer.read(row, column + 1)));
+                self.buffer
+                    .write(row, column + 1, template.set_placeholder());
+            }
+            column += width;
+        }
+    }
+
+    fn update_hardware_cursor(&mut self) {
+        if let Some(cursor) = CONFIG.hardware_cursor.lock().as_mut() {
+            let visible = self.mode.contains(TerminalMode::SHOW_CURSOR) && self.buffer.is_latest();
+            cursor.set_cursor_visible(visible);
+
+            if self.buffer.is_empty() {
+                return;
+            }
+            if let Some((font_width, font_height)) = CONFIG.with_font_manager(|f| f.size()) {
+                let row = min(self.cursor.row, self.buffer.height() - 1);
+                let column = min(self.cursor.column, self.buffer.width() - 1);
+                cursor.set_cursor_position(column * font_width, row * font_height);
+            }
+        }
+    }
+
+    fn index(&mut self) {
+        if self.cursor.row == self.scroll_region.1 {
+            self.scroll_up(1);
+        } else if self.cursor.row < self.buffer.height() - 1 {
+            self.cursor.row += 1;
+        }
+    }
+
+    fn origin_region(&self) -> (usize, usize) {
+        if self.mode.contains(TerminalMode::ORIGIN) {
+            self.scroll_region
+        } else {
+            (0, self.buffer.height() - 1)
+        }
+    }
+
     fn scroll_history_up(&mut self, count: usize) {
         log!("Scroll up with buffer: {}", count);
         self.buffer.scroll_history(count, true);
@@ -238,28 +978,136 @@ impl<D: DrawTarget> TerminalInner<D> {
         self.buffer.scroll_history(count, false);
     }
 
+    fn scroll_from_cursor(&mut self, count: usize, is_up: bool) {
+        let (top, bottom) = self.scroll_region;
+        if self.cursor.row < top || self.cursor.row > bottom {
+            return;
+        }
+
+        let region = (self.cursor.row, bottom);
+        let count = min(count, bottom - self.cursor.row + 1);
+        self.buffer
+            .scroll(count, self.attribute_template.clear(), is_up, region);
+    }
+
+    fn soft_reset(&mut self) {
+        log!("Soft reset");
+        self.mode.insert(TerminalMode::SHOW_CURSOR);
+        self.mode.remove(
+            TerminalMode::INSERT
+                | TerminalMode::ORIGIN
+                | TerminalMode::APP_CURSOR
+                | TerminalMode::APP_KEYPAD,
+        );
+        self.keyboard.set_app_cursor(false);
+        self.scroll_region = (0, self.buffer.height().saturating_sub(1));
+        self.attribute_template = self.default_cell();
+        self.charsets = [StandardCharset::Ascii; 4];
+        self.active_charset = CharsetIndex::G0;
+        self.pending_single_shift = None;
+        self.saved_cursor = SavedCursorState {
+            cursor: Cursor {
+                shape: self.cursor.shape,
+                ..Cursor::default()
+            },
+            ..self.saved_state()
+        };
+    }
+
+    fn saved_state(&self) -> SavedCursorState {
+        SavedCursorState {
+            cursor: self.cursor,
+            mode: self.mode & (TerminalMode::ORIGIN | TerminalMode::LINE_WRAP),
+            attribute_template: self.attribute_template,
+            charsets: self.charsets,
+            active_charset: self.active_charset,
+        }
+    }
+
+    fn restore_state(&mut self, saved: SavedCursorState) {
+        self.cursor = saved.cursor;
+        self.mode
+            .remove(TerminalMode::ORIGIN | TerminalMode::LINE_WRAP);
+        self.mode
+            .insert(saved.mode & (TerminalMode::ORIGIN | TerminalMode::LINE_WRAP));
+        self.attribute_template = saved.attribute_template;
+        self.charsets = saved.charsets;
+        self.active_charset = saved.active_charset;
+    }
+
+    fn vt52_dispatch(&mut self, byte: u8) {
+        log!("VT52 escape: {:?}", byte as char);
+        match byte {
+            b'A' => self.move_up(1),
+            b'B' => self.move_down(1),
+            b'C' => self.move_forward(1),
+            b'D' => self.move_backward(1),
+            b'F' => self.configure_charset(
+                CharsetIndex::G0,
+                StandardCharset::SpecialCharacterAndLineDrawing,
+            ),
+            b'G' => self.configure_charset(CharsetIndex::G0, StandardCharset::Ascii),
+            b'H' => self.goto(0, 0),
+            b'I' => self.reverse_index(),
+            b'J' => self.clear_screen(ClearMode::Below),
+            b'K' => self.clear_line(LineClearMode::Right),
+            b'Z' => self.pty_write_bytes(b"\x1b/Z"),
+            b'=' => self.set_keypad_application_mode(),
+            b'>' => self.unset_keypad_application_mode(),
+            b'<' => self.mode.remove(TerminalMode::VT52),
+            _ => log!("Unhandled VT52 escape: {:?}", byte as char),
+        }
+    }
+
+    fn line_width(&self) -> usize {
+        match self.buffer.line_attr(self.cursor.row) {
+            LineAttr::Normal => self.buffer.width(),
+            _ => self.buffer.width() / 2,
+        }
+    }
+
+    fn default_cell(&self) -> Cell {
+        self.default_cell.unwrap_or_default()
+    }
+
     fn swap_alt_screen(&mut self) {
         self.mode ^= TerminalMode::ALT_SCREEN;
         swap(&mut self.cursor, &mut self.alt_cursor);
         self.buffer.swap_alt_screen(self.attribute_template);
 
-        if !self.mode.contains(TerminalMode::ALT_SCREEN) {
-            self.saved_cursor = self.cursor;
-            self.attribute_template = Cell::default();
+        if self.mode.contains(TerminalMode::ALT_SCREEN) {
+            self.saved_scroll_region = self.scroll_region;
+        } else {
+            self.scroll_region = self.saved_scroll_region;
+            self.saved_cursor.cursor = self.cursor;
+            self.attribute_template = self.default_cell();
         }
     }
 }
 
+fn secondary_da(type_id: u16, version: &str, options: u8) -> String {
+    let mut parts = version
+        .split('.')
+        .map(|part| part.parse::<u32>().unwrap_or(0));
+    let version = (0..3).fold(0, |packed, _| packed * 100 + parts.next().unwrap_or(0));
+    format!("\x1b[>{};{};{}c", type_id, version, options)
+}
+
 impl<D: DrawTarget> Handler for TerminalInner<D> {
     fn set_title(&mut self, title: Option<String>) {
-        log!("Unhandled set_title: {:?}", title);
+        log!("Set title: {:?}", title);
+        self.title = title.unwrap_or_default();
+        CONFIG
+            .title_handler
+            .lock()
+            .map(|handler| handler(&self.title));
     }
 
     fn set_cursor_style(&mut self, style: Option<CursorStyle>) {
         log!("Set cursor style: {:?}", style);
-        if let Some(style) = style {
-            self.set_cursor_shape(style.shape);
-        }
+        let style = style.unwrap_or_default();
+        self.set_cursor_shape(style.shape);
+        self.cursor_blink_requested = style.blinking;
     }
 
     fn set_cursor_shape(&mut self, shape: CursorShape) {
@@ -268,10 +1116,19 @@ impl<D: DrawTarget> Handler for TerminalInner<D> {
     }
 
     fn input(&mut self, content: char) {
+        let charset = self.pending_single_shift.take();
+        let charset = charset.unwrap_or(self.active_charset);
+        let content = self.charsets[charset as usize].map(content);
+
         let template = self.attribute_template.set_content(content);
         let width = if template.wide { 2 } else { 1 };
 
-        if self.cursor.column + width > self.buffer.width() {
+        if width > self.line_width() {
+            log!("No room for wide character: {:?}", content);
+            return;
+        }
+
+        if self.cursor.column + width > self.line_width() {
             if !self.mode.contains(TerminalMode::LINE_WRAP) {
                 return;
             }
@@ -294,8 +1151,9 @@ impl<D: DrawTarget> Handler for TerminalInner<D> {
     }
 
     fn goto(&mut self, row: i32, col: usize) {
-        self.cursor.row = min(row as usize, self.buffer.height());
-        self.cursor.column = min(col, self.buffer.width());
+        let (top, bottom) = self.origin_region();
+        self.cursor.row = min(top + row.max(0) as usize, bottom);
+        self.cursor.column = min(col, self.buffer.width() - 1);
     }
 
     fn goto_line(&mut self, row: i32) {
@@ -305,42 +1163,69 @@ impl<D: DrawTarget> Handler for TerminalInner<D> {
 
     fn goto_col(&mut self, col: usize) {
         log!("Goto column: {}", col);
-        self.goto(self.cursor.row as i32, col);
+        self.cursor.column = min(col, self.buffer.width() - 1);
     }
 
     fn insert_blank(&mut self, count: usize) {
         log!("Insert blank: {}", count);
         let (row, columns) = (self.cursor.row, self.buffer.width());
+        self.cursor.column = min(self.cursor.column, columns - 1);
         let count = min(count, columns - self.cursor.column);
 
         let template = self.attribute_template.clear();
-        for column in (self.cursor.column..columns - count).rev() {
+        let start = self.cursor.column;
+        if start > 0 && self.buffer.read(row, start).placeholder {
+            self.buffer.write(row, start - 1, template);
+            self.buffer.write(row, start, template);
+        }
+
+        for column in (start + count..columns).rev() {
             self.buffer
-                .write(row, column + count, self.buffer.read(row, column));
+                .write(row, column, self.buffer.read(row, column - count));
+        }
+        for column in start..start + count {
             self.buffer.write(row, column, template);
         }
+
+        let last = self.buffer.read(row, columns - 1);
+        if last.wide && !last.placeholder {
+            self.buffer.write(row, columns - 1, template);
+        }
     }
 
     fn move_up(&mut self, rows: usize) {
         log!("Move up: {}", rows);
-        self.goto(
-            self.cursor.row.saturating_sub(rows) as i32,
-            self.cursor.column,
-        );
+        self.cursor.row = self.cursor.row.saturating_sub(rows);
+        self.cursor.column = min(self.cursor.column, self.buffer.width() - 1);
     }
 
     fn move_down(&mut self, rows: usize) {
         log!("Move down: {}", rows);
-        let goto_line = min(self.cursor.row + rows, self.buffer.height() - 1) as _;
-        self.goto(goto_line, self.cursor.column);
+        self.cursor.row = min(self.cursor.row + rows, self.buffer.height() - 1);
+        self.cursor.column = min(self.cursor.column, self.buffer.width() - 1);
     }
 
     fn identify_terminal(&mut self, intermediate: Option<char>) {
-        log!("Unhandled identify terminal: {:?}", intermediate);
+        log!("Identify terminal: {:?}", intermediate);
+        match intermediate {
+            None => self.pty_write_bytes(b"\x1b[?6c"),
+            Some('>') => self.pty_write(&self.secondary_da.clone()),
+            _ => log!("Unhandled identify terminal: {:?}", intermediate),
+        }
     }
 
     fn device_status(&mut self, status: usize) {
-        log!("Unhandled device_status: {}", status);
+        log!("Device status: {}", status);
+        match status {
+            5 => self.pty_write_bytes(b"\x1b[0n"),
+            6 => {
+                let top = self.origin_region().0;
+                let row = self.cursor.row.saturating_sub(top) + 1;
+                let column = self.cursor.column + 1;
+                self.pty_write(&format!("\x1b[{};{}R", row, column));
+            }
+            _ => log!("Unhandled device_status: {}", status),
+        }
     }
 
     fn move_forward(&mut self, cols: usize) {
@@ -350,24 +1235,31 @@ impl<D: DrawTarget> Handler for TerminalInner<D> {
 
     fn move_backward(&mut self, cols: usize) {
         log!("Move backward: {}", cols);
-        self.cursor.column = self.cursor.column.saturating_sub(cols);
+        for _ in 0..cols {
+            self.backspace();
+        }
     }
 
     fn move_up_and_cr(&mut self, rows: usize) {
         log!("Move up and cr: {}", rows);
-        self.goto(self.cursor.row.saturating_sub(rows) as i32, 0);
+        self.move_up(rows);
+        self.cursor.column = 0;
     }
 
     fn move_down_and_cr(&mut self, rows: usize) {
         log!("Move down and cr: {}", rows);
-        let goto_line = min(self.cursor.row + rows, self.buffer.height() - 1);
-        self.goto(goto_line as i32, 0);
+        self.move_down(rows);
+        self.cursor.column = 0;
     }
 
     fn put_tab(&mut self, count: u16) {
         log!("Put tab: {}", count);
+        if self.cursor.column >= self.buffer.width() {
+            return;
+        }
+
         for _ in 0..count {
-            let tab_stop = self.cursor.column.div_ceil(8) * 8;
+            let tab_stop = (self.cursor.column / 8 + 1) * 8;
             let end_column = tab_stop.min(self.buffer.width());
             let template = self.attribute_template.clear();
 
@@ -380,7 +1272,13 @@ impl<D: DrawTarget> Handler for TerminalInner<D> {
     }
 
     fn backspace(&mut self) {
-        self.cursor.column = self.cursor.column.saturating_sub(1);
+        let column = min(self.cursor.column, self.buffer.width());
+        if column == 0 && self.cursor.row > 0 && self.mode.contains(TerminalMode::REVERSE_WRAP) {
+            self.cursor.row -= 1;
+            self.cursor.column = self.buffer.width() - 1;
+        } else {
+            self.cursor.column = column.saturating_sub(1);
+        }
     }
 
     fn carriage_return(&mut self) {
@@ -388,15 +1286,12 @@ impl<D: DrawTarget> Handler for TerminalInner<D> {
     }
 
     fn linefeed(&mut self) {
-        if CONFIG.auto_crnl.load(Ordering::Relaxed) {
+        if CONFIG.auto_crnl.load(Ordering::Relaxed)
+            || self.mode.contains(TerminalMode::LINE_FEED_NEW_LINE)
+        {
             self.carriage_return();
         }
-
-        if self.cursor.row == self.scroll_region.1 {
-            self.scroll_up(1);
-        } else if self.cursor.row < self.buffer.height() - 1 {
-            self.cursor.row += 1;
-        }
+        self.index();
     }
 
     fn bell(&mut self) {
@@ -410,11 +1305,8 @@ impl<D: DrawTarget> Handler for TerminalInner<D> {
 
     fn newline(&mut self) {
         log!("Newline!");
-        self.linefeed();
-
-        if self.mode.contains(TerminalMode::LINE_FEED_NEW_LINE) {
-            self.carriage_return();
-        }
+        self.index();
+        self.carriage_return();
     }
 
     fn set_horizontal_tabstop(&mut self) {
@@ -435,12 +1327,12 @@ impl<D: DrawTarget> Handler for TerminalInner<D> {
 
     fn insert_blank_lines(&mut self, count: usize) {
         log!("Insert blank lines: {}", count);
-        self.scroll_down(count);
+        self.scroll_from_cursor(count, false);
     }
 
     fn delete_lines(&mut self, count: usize) {
         log!("Delete lines: {}", count);
-        self.scroll_up(count);
+        self.scroll_from_cursor(count, true);
     }
 
     fn erase_chars(&mut self, count: usize) {
@@ -457,13 +1349,15 @@ impl<D: DrawTarget> Handler for TerminalInner<D> {
     fn delete_chars(&mut self, count: usize) {
         log!("Delete chars: {}", count);
         let (row, columns) = (self.cursor.row, self.buffer.width());
-        let count = min(count, columns - self.cursor.column - 1);
+        self.cursor.column = min(self.cursor.column, columns - 1);
 
         let template = self.attribute_template.clear();
-        for column in (self.cursor.column + count)..columns {
-            self.buffer
-                .write(row, column - count, self.buffer.read(row, column));
-            self.buffer.write(row, column, template);
+        for column in self.cursor.column..columns {
+            let cell = match column.checked_add(count) {
+                Some(source) if source < columns => self.buffer.read(row, source),
+                _ => template,
+            };
+            self.buffer.write(row, column, cell);
         }
     }
 
@@ -477,12 +1371,12 @@ impl<D: DrawTarget> Handler for TerminalInner<D> {
 
     fn save_cursor_position(&mut self) {
         log!("Save cursor position");
-        self.saved_cursor = self.cursor;
+        self.saved_cursor = self.saved_state();
     }
 
     fn restore_cursor_position(&mut self) {
         log!("Restore cursor position");
-        self.cursor = self.saved_cursor;
+        self.restore_state(self.saved_cursor);
     }
 
     fn clear_line(&mut self, mode: LineClearMode) {
@@ -531,10 +1425,7 @@ impl<D: DrawTarget> Handler for TerminalInner<D> {
                     }
                 }
             }
-            ClearMode::All => {
-                self.buffer.clear(template);
-                self.cursor = Cursor::default();
-            }
+            ClearMode::All => self.buffer.clear(template),
             ClearMode::Saved => {
                 self.buffer.clear(template);
                 self.cursor = Cursor::default();
@@ -549,15 +1440,9 @@ impl<D: DrawTarget> Handler for TerminalInner<D> {
 
     fn reset_state(&mut self) {
         log!("Reset state");
-        if self.mode.contains(TerminalMode::ALT_SCREEN) {
-            self.swap_alt_screen();
-        }
-        self.buffer.clear(Cell::default());
-        self.cursor = Cursor::default();
-        self.saved_cursor = self.cursor;
+        self.reset_screen();
         self.buffer.clear_history();
-        self.mode = TerminalMode::default();
-        self.attribute_template = Cell::default();
+        self.cursor_blink_requested = false;
     }
 
     fn reverse_index(&mut self) {
@@ -579,7 +1464,7 @@ impl<D: DrawTarget> Handler for TerminalInner<D> {
         match attr {
             Attr::Foreground(color) => self.attribute_template.foreground = handle_color(color),
             Attr::Background(color) => self.attribute_template.background = handle_color(color),
-            Attr::Reset => self.attribute_template = Cell::default(),
+            Attr::Reset => self.attribute_template = self.default_cell(),
             Attr::Reverse => self.attribute_template.flags |= Flags::INVERSE,
             Attr::CancelReverse => self.attribute_template.flags.remove(Flags::INVERSE),
             Attr::Bold => self.attribute_template.flags.insert(Flags::BOLD),
@@ -588,7 +1473,20 @@ impl<D: DrawTarget> Handler for TerminalInner<D> {
             Attr::Italic => self.attribute_template.flags.insert(Flags::ITALIC),
             Attr::CancelItalic => self.attribute_template.flags.remove(Flags::ITALIC),
             Attr::Underline => self.attribute_template.flags.insert(Flags::UNDERLINE),
-            Attr::CancelUnderline => self.attribute_template.flags.remove(Flags::UNDERLINE),
+            Attr::DoubleUnderline => self
+                .attribute_template
+                .flags
+                .insert(Flags::DOUBLE_UNDERLINE),
+            Attr::Undercurl => self.attribute_template.flags.insert(Flags::CURLY_UNDERLINE),
+            Attr::CancelUnderline => self
+                .attribute_template
+                .flags
+                .remove(Flags::UNDERLINE | Flags::DOUBLE_UNDERLINE | Flags::CURLY_UNDERLINE),
+            Attr::Strike => self.attribute_template.flags.insert(Flags::STRIKETHROUGH),
+            Attr::CancelStrike => self.attribute_template.flags.remove(Flags::STRIKETHROUGH),
+            Attr::BlinkSlow => self.attribute_template.flags.insert(Flags::BLINK),
+            Attr::BlinkFast => self.attribute_template.flags.insert(Flags::BLINK),
+            Attr::CancelBlink => self.attribute_template.flags.remove(Flags::BLINK),
             Attr::Hidden => self.attribute_template.flags.insert(Flags::HIDDEN),
             Attr::CancelHidden => self.attribute_template.flags.remove(Flags::HIDDEN),
             _ => log!("Unhandled terminal attribute: {:?}", attr),
@@ -604,10 +1502,7 @@ impl<D: DrawTarget> Handler for TerminalInner<D> {
             }
         };
 
-        match mode {
-            NamedMode::Insert => self.mode.insert(TerminalMode::INSERT),
-            NamedMode::LineFeedNewLine => self.mode.insert(TerminalMode::LINE_FEED_NEW_LINE),
-        }
+        self.mode.insert(mode_flag(mode));
     }
 
     fn unset_mode(&mut self, mode: Mode) {
@@ -619,19 +1514,31 @@ impl<D: DrawTarget> Handler for TerminalInner<D> {
             }
         };
 
-        match mode {
-            NamedMode::Insert => self.mode.remove(TerminalMode::INSERT),
-            NamedMode::LineFeedNewLine => self.mode.remove(TerminalMode::LINE_FEED_NEW_LINE),
-        }
+        self.mode.remove(mode_flag(mode));
     }
 
     fn report_mode(&mut self, mode: Mode) {
-        log!("Unhandled report mode: {:?}", mode);
+        log!("Report mode: {:?}", mode);
+        let state = match mode {
+            Mode::Named(mode) => {
+                if self.mode.contains(mode_flag(mode)) {
+                    1
+                } else {
+                    2
+                }
+            }
+            Mode::Unknown(_) => 0,
+        };
+        self.pty_write(&format!("\x1b[{};{}$y", mode.raw(), state));
     }
 
     fn set_private_mode(&mut self, mode: PrivateMode) {
         let mode = match mode {
             PrivateMode::Named(mode) => mode,
+            PrivateMode::Unknown(45) => {
+                self.mode.insert(TerminalMode::REVERSE_WRAP);
+                return;
+            }
             PrivateMode::Unknown(mode) => {
                 log!("Ignoring unknown mode {} in set_private_mode", mode);
                 return;
@@ -641,6 +1548,7 @@ impl<D: DrawTarget> Handler for TerminalInner<D> {
         match mode {
             NamedPrivateMode::SwapScreenAndSetRestoreCursor => {
                 if !self.mode.contains(TerminalMode::ALT_SCREEN) {
+                    self.alt_saved_state = self.saved_state();
                     self.swap_alt_screen();
                 }
             }
@@ -650,13 +1558,31 @@ impl<D: DrawTarget> Handler for TerminalInner<D> {
                 self.keyboard.set_app_cursor(true);
             }
             NamedPrivateMode::LineWrap => self.mode.insert(TerminalMode::LINE_WRAP),
-            _ => log!("Unhandled set mode: {:?}", mode),
+            NamedPrivateMode::Origin => {
+                self.mode.insert(TerminalMode::ORIGIN);
+                self.goto(0, 0);
+            }
+            NamedPrivateMode::BracketedPaste => self.mode.insert(TerminalMode::BRACKETED_PASTE),
+            NamedPrivateMode::SyncUpdate => self.synced_output = true,
+            mode => match private_mode_flag(mode) {
+                Some(flag) => self.mode.insert(flag),
+                None => log!("Unhandled set mode: {:?}", mode),
+            },
         }
     }
 
     fn unset_private_mode(&mut self, mode: PrivateMode) {
         let mode = match mode {
             PrivateMode::Named(mode) => mode,
+            PrivateMode::Unknown(2) => {
+                log!("Enter VT52 mode");
+                self.mode.insert(TerminalMode::VT52);
+                return;
+            }
+            PrivateMode::Unknown(45) => {
+                self.mode.remove(TerminalMode::REVERSE_WRAP);
+                return;
+            }
             PrivateMode::Unknown(mode) => {
                 log!("Ignoring unknown mode {} in unset_private_mode", mode);
                 return;
@@ -667,6 +1593,7 @@ impl<D: DrawTarget> Handler for TerminalInner<D> {
             NamedPrivateMode::SwapScreenAndSetRestoreCursor => {
                 if self.mode.contains(TerminalMode::ALT_SCREEN) {
                     self.swap_alt_screen();
+                    self.restore_state(self.alt_saved_state);
                 }
             }
             NamedPrivateMode::ShowCursor => self.mode.remove(TerminalMode::SHOW_CURSOR),
@@ -675,12 +1602,49 @@ impl<D: DrawTarget> Handler for TerminalInner<D> {
                 self.keyboard.set_app_cursor(false);
             }
             NamedPrivateMode::LineWrap => self.mode.remove(TerminalMode::LINE_WRAP),
-            _ => log!("Unhandled unset mode: {:?}", mode),
+            NamedPrivateMode::Origin => {
+                self.mode.remove(TerminalMode::ORIGIN);
+                self.goto(0, 0);
+            }
+            NamedPrivateMode::BracketedPaste => self.mode.remove(TerminalMode::BRACKETED_PASTE),
+            NamedPrivateMode::SyncUpdate => self.synced_output = false,
+            mode => match private_mode_flag(mode) {
+                Some(flag) => self.mode.remove(flag),
+                None => log!("Unhandled unset mode: {:?}", mode),
+            },
         }
     }
 
     fn report_private_mode(&mut self, mode: PrivateMode) {
-        log!("Unhandled report private mode: {:?}", mode);
+        log!("Report private mode: {:?}", mode);
+        let state = match mode {
+            PrivateMode::Named(NamedPrivateMode::SyncUpdate) => {
+                if self.synced_output {
+                    1
+                } else {
+                    2
+                }
+            }
+            PrivateMode::Named(mode) => match private_mode_flag(mode) {
+                Some(flag) => {
+                    if self.mode.contains(flag) {
+                        1
+                    } else {
+                        2
+                    }
+                }
+                None => 0,
+            },
+            PrivateMode::Unknown(45) => {
+                if self.mode.contains(TerminalMode::REVERSE_WRAP) {
+                    1
+                } else {
+                    2
+                }
+            }
+            PrivateMode::Unknown(_) => 0,
+        };
+        self.pty_write(&format!("\x1b[?{};{}$y", mode.raw(), state));
     }
 
     fn set_scrolling_region(&mut self, top: usize, bottom: Option<usize>) {
@@ -708,48 +1672,114 @@ impl<D: DrawTarget> Handler for TerminalInner<D> {
     }
 
     fn set_active_charset(&mut self, index: CharsetIndex) {
-        log!("Unhandled set active charset: {:?}", index);
+        log!("Set active charset: {:?}", index);
+        self.active_charset = index;
     }
 
     fn configure_charset(&mut self, index: CharsetIndex, charset: StandardCharset) {
-        log!("Unhandled configure charset: {:?}, {:?}", index, charset);
+        log!("Configure charset: {:?}, {:?}", index, charset);
+        self.charsets[index as usize] = charset;
     }
 
-    fn set_color(&mut self, index: usize, color: Rgb) {
-        log!("Unhandled set color: {}, {:?}", index, color);
+    fn set_color(&mut self, index: usize, color: AnsiRgb) {
+        log!("Set color: {}, {:?}", index, color);
+        let mut color_scheme = CONFIG.color_scheme.lock();
+        let Some(ansi_color) = color_scheme.ansi_colors.get_mut(index) else {
+            log!("Unhandled set color: {}, {:?}", index, color);
+            return;
+        };
+        if *ansi_color == (color.r, color.g, color.b) {
+            return;
+        }
+        *ansi_color = (color.r, color.g, color.b);
+        drop(color_scheme);
+        self.buffer.invalidate();
     }
 
     fn dynamic_color_sequence(&mut self, prefix: String, index: usize, terminator: &str) {
         log!(
-            "Unhandled dynamic color sequence: {}, {}, {}",
+            "Dynamic color sequence: {}, {}, {}",
             prefix,
             index,
             terminator
         );
+        if index > 257 {
+            log!("Unhandled dynamic color sequence: {}, {}", prefix, index);
+            return;
+        }
+        let (r, g, b) = Color::Indexed(index as u16).to_rgb();
+        self.pty_write(&format!(
+            "\x1b]{};rgb:{:02x}{:02x}/{:02x}{:02x}/{:02x}{:02x}{}",
+            prefix, r, r, g, g, b, b, terminator
+        ));
     }
 
     fn reset_color(&mut self, index: usize) {
         log!("Unhandled reset color: {}", index);
     }
 
-    fn clipboard_store(&mut self, clipboard: u8, base64: &[u8]) {
-        log!("Unhandled clipboard store: {}, {:?}", clipboard, base64);
+    fn clipboard_store(&mut self, clipboard: u8, data: &[u8]) {
+        log!(
+            "Clipboard store: {}, {} bytes",
+            clipboard as char,
+            data.len()
+        );
+        let mut text = Vec::new();
+        if !base64::decode(data, &mut text) {
+            return;
+        }
+        let Ok(text) = String::from_utf8(text) else {
+            return;
+        };
+        if let Some(handler) = CONFIG.clipboard.lock().as_mut() {
+            match clipboard {
+                b'p' | b's' => handler.set_primary_selection(text),
+                _ => handler.set_text(text),
+            }
+        }
     }
 
     fn clipboard_load(&mut self, clipboard: u8, terminator: &str) {
-        log!("Unhandled clipboard load: {}, {}", clipboard, terminator);
+        log!("Clipboard load: {}", clipboard as char);
+        let text = CONFIG
+            .clipboard
+            .lock()
+            .as_mut()
+            .and_then(|handler| match clipboard {
+                b'p' | b's' => handler.get_primary_selection(),
+                _ => handler.get_text(),
+            });
+        if let Some(text) = text {
+            let data = base64::encode(text.as_bytes());
+            self.pty_write(&format!(
+                "\x1b]52;{};{}{}",
+                clipboard as char, data, terminator
+            ));
+        }
     }
 
     fn decaln(&mut self) {
-        log!("Unhandled decaln!");
+        log!("Screen alignment test");
+        self.buffer.clear(Cell::default().set_content('E'));
+        self.scroll_region = (0, self.buffer.height().saturating_sub(1));
+        self.mode.remove(TerminalMode::ORIGIN);
+        self.cursor.row = 0;
+        self.cursor.column = 0;
     }
 
     fn push_title(&mut self) {
-        log!("Unhandled push title!");
+        log!("Push title: {:?}", self.title);
+        if self.title_stack.len() == TITLE_STACK_SIZE {
+            self.title_stack.remove(0);
+        }
+        self.title_stack.push(self.title.clone());
     }
 
     fn pop_title(&mut self) {
-        log!("Unhandled pop title!");
+        log!("Pop title");
+        if let Some(title) = self.title_stack.pop() {
+            self.set_title(Some(title));
+        }
     }
 
     fn text_area_size_pixels(&mut self) {
@@ -776,3 +1806,26 @@ impl<D: DrawTarget> Handler for TerminalInner<D> {
         log!("Unhandled pop keyboard modes: {}", to_pop);
     }
 }
+
+fn mode_flag(mode: NamedMode) -> TerminalMode {
+    match mode {
+        NamedMode::Insert => TerminalMode::INSERT,
+        NamedMode::LineFeedNewLine => TerminalMode::LINE_FEED_NEW_LINE,
+    }
+}
+
+fn private_mode_flag(mode: NamedPrivateMode) -> Option<TerminalMode> {
+    match mode {
+        NamedPrivateMode::CursorKeys => Some(TerminalMode::APP_CURSOR),
+        NamedPrivateMode::LineWrap => Some(TerminalMode::LINE_WRAP),
+        NamedPrivateMode::Origin => Some(TerminalMode::ORIGIN),
+        NamedPrivateMode::ShowCursor => Some(TerminalMode::SHOW_CURSOR),
+        NamedPrivateMode::BracketedPaste => Some(TerminalMode::BRACKETED_PASTE),
+        NamedPrivateMode::SwapScreenAndSetRestoreCursor => Some(TerminalMode::ALT_SCREEN),
+        NamedPrivateMode::ReportMouseClicks => Some(TerminalMode::MOUSE_REPORT_CLICK),
+        NamedPrivateMode::ReportCellMouseMotion => Some(TerminalMode::MOUSE_DRAG),
+        NamedPrivateMode::ReportAllMouseMotion => Some(TerminalMode::MOUSE_MOTION),
+        NamedPrivateMode::SgrMouse => Some(TerminalMode::SGR_MOUSE),
+        _ => None,
+    }
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use common::TestTerminal;
+use os_terminal::{ClipboardHandler, MouseButton, MouseInput};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum ClipboardRegister {
+    Clipboard,
+    Primary,
+}
+
+#[derive(Clone, Default)]
+struct MemoryClipboard(Arc<Mutex<HashMap<ClipboardRegister, String>>>);
+
+impl MemoryClipboard {
+    fn get(&self, register: ClipboardRegister) -> Option<String> {
+        self.0.lock().unwrap().get(&register).cloned()
+    }
+
+    fn set(&self, register: ClipboardRegister, text: &str) {
+        self.0.lock().unwrap().insert(register, text.to_string());
+    }
+}
+
+impl ClipboardHandler for MemoryClipboard {
+    fn get_text(&mut self) -> Option<String> {
+        self.get(ClipboardRegister::Clipboard)
+    }
+
+    fn set_text(&mut self, text: String) {
+        self.set(ClipboardRegister::Clipboard, &text);
+    }
+
+    fn get_primary_selection(&mut self) -> Option<String> {
+        self.get(ClipboardRegister::Primary)
+    }
+
+    fn set_primary_selection(&mut self, text: String) {
+        self.set(ClipboardRegister::Primary, &text);
+    }
+}
+
+fn setup() -> (TestTerminal, MemoryClipboard) {
+    let mut test = TestTerminal::new();
+    let clipboard = MemoryClipboard::default();
+    test.terminal.set_clipboard(Box::new(clipboard.clone()));
+    (test, clipboard)
+}
+
+#[test]
+fn osc52_store() {
+    let (mut test, clipboard) = setup();
+    test.terminal.process(b"\x1b]52;c;SGVsbG8=\x07");
+    assert_eq!(
+        clipboard.get(ClipboardRegister::Clipboard).as_deref(),
+        Some("Hello")
+    );
+    assert_eq!(clipboard.get(ClipboardRegister::Primary), None);
+
+    test.terminal.process(b"\x1b]52;p;V29ybGQ=\x1b\\");
+    assert_eq!(
+        clipboard.get(ClipboardRegister::Primary).as_deref(),
+        Some("World")
+    );
+}
+
+#[test]
+fn osc52_store_invalid_base64() {
+    let (mut test, clipboard) = setup();
+    test.terminal.process(b"\x1b]52;c;!!!\x07");
+    assert_eq!(clipboard.get(ClipboardRegister::Clipboard), None);
+}
+
+#[test]
+fn osc52_load() {
+    let (mut test, clipboard) = setup();
+    clipboard.set(ClipboardRegister::Clipboard, "Hello");
+    test.terminal.process(b"\x1b]52;c;?\x07");
+    assert_eq!(test.take_output(), "\x1b]52;c;SGVsbG8=\x07");
+
+    clipboard.set(ClipboardRegister::Primary, "Hi");
+    test.terminal.process(b"\x1b]52;p;?\x1b\\");
+    assert_eq!(test.take_output(), "\x1b]52;p;SGk=\x1b\\");
+}
+
+#[test]
+fn osc52_round_trip() {
+    let (mut test, _clipboard) = setup();
+    test.terminal
+        .process(b"\x1b]52;c;b3MtdGVybWluYWw=\x07\x1b]52;c;?\x07");
+    assert_eq!(test.take_output(), "\x1b]52;c;b3MtdGVybWluYWw=\x07");
+}
+
+#[test]
+fn middle_click_paste() {
+    let (mut test, clipboard) = setup();
+    clipboard.set(ClipboardRegister::Primary, "echo hi");
+
+    let paste = test
+        .terminal
+        .handle_mouse(MouseInput::Pressed(MouseButton::Middle));
+    assert_eq!(paste.as_deref(), Some("echo hi"));
+
+    test.terminal.process(b"\x1b[?2004h");
+    let paste = test
+        .terminal
+        .handle_mouse(MouseInput::Pressed(MouseButton::Middle));
+    assert_eq!(paste.as_deref(), Some("\x1b[200~echo hi\x1b[201~"));
+
+    test.terminal.set_middle_click_paste(false);
+    let paste = test
+        .terminal
+        .handle_mouse(MouseInput::Pressed(MouseButton::Middle));
+    assert_eq!(paste, None);
+}
+
+#[test]
+fn copy_shortcut_copies_screen() {
+    let (mut test, clipboard) = setup();
+    test.terminal
+        .process(b"\x1bcfirst line  \r\n\r\n\x1b[31msecond\x1b[0m");
+
+    let output: String = [0x1d, 0x2a, 0x2e, 0xae, 0xaa, 0x9d]
+        .into_iter()
+        .filter_map(|scancode| test.terminal.handle_keyboard(scancode))
+        .collect();
+    assert_eq!(output, "");
+    assert_eq!(
+        clipboard.get(ClipboardRegister::Clipboard).as_deref(),
+        Some("first line\n\nsecond")
+    );
+}
@@ -0,0 +1,90 @@
+#![allow(dead_code)]
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use os_terminal::font::BitmapFont;
+use os_terminal::{DrawTarget, Rgb, Terminal};
+
+pub const WIDTH: usize = 800;
+pub const HEIGHT: usize = 600;
+
+static SERIAL: Mutex<()> = Mutex::new(());
+
+pub struct DummyDisplay {
+    pub buffer: Vec<Rgb>,
+}
+
+impl DrawTarget for DummyDisplay {
+    fn size(&self) -> (usize, usize) {
+        (WIDTH, HEIGHT)
+    }
+
+    fn draw_pixel(&mut self, x: usize, y: usize, color: Rgb) {
+        self.buffer[y * WIDTH + x] = color;
+    }
+}
+
+pub struct TestTerminal {
+    pub terminal: Terminal<DummyDisplay>,
+    pub output: Arc<Mutex<String>>,
+    _serial: MutexGuard<'static, ()>,
+}
+
+impl TestTerminal {
+    pub fn new() -> Self {
+        let serial = SERIAL.lock().unwrap_or_else(|error| error.into_inner());
+
+        let display = DummyDisplay {
+            buffer: vec![(0, 0, 0); WIDTH * HEIGHT],
+        };
+        let mut terminal = Terminal::new(display);
+        terminal.set_font_manager(Box::new(BitmapFont));
+
+        let output = Arc::new(Mutex::new(String::new()));
+        let writer = output.clone();
+        terminal.set_pty_writer(Box::new(move |data: &str| {
+            writer.lock().unwrap().push_str(data);
+        }));
+
+        Self {
+            terminal,
+            output,
+            _serial: serial,
+        }
+    }
+
+    pub fn take_output(&self) -> String {
+        core::mem::take(&mut *self.output.lock().unwrap())
+    }
+
+    pub fn cursor_position(&mut self) -> (usize, usize) {
+        self.take_output();
+        self.terminal.process(b"\x1b[6n");
+        let report = self.take_output();
+        let position = report
+            .strip_prefix("\x1b[")
+            .and_then(|report| report.strip_suffix('R'))
+            .expect("invalid cursor position report");
+        let (row, column) = position.split_once(';').unwrap();
+        let (row, column) = (
+            row.parse::<usize>().unwrap(),
+            column.parse::<usize>().unwrap(),
+        );
+        (row - 1, column - 1)
+    }
+
+    pub fn screen(&self) -> Vec<String> {
+        (0..self.terminal.rows())
+            .map(|row| self.line(row))
+            .collect()
+    }
+
+    pub fn line(&self, row: usize) -> String {
+        let content = self.terminal.accessible_text();
+        let line = content.lines[row]
+            .spans
+            .iter()
+            .map(|span| span.text.as_str());
+        line.collect::<String>().trim_end().to_string()
+    }
+}
@@ -0,0 +1,28 @@
+mod common;
+
+use common::TestTerminal;
+
+#[test]
+fn erase_chars_keeps_cursor() {
+    let mut test = TestTerminal::new();
+    test.terminal
+        .process(b"\x1bc\x1b[2;1Habcdefghijkl\x1b[2;4H");
+    let position = test.cursor_position();
+
+    test.terminal.process(b"\x1b[5X");
+    assert_eq!(test.cursor_position(), position);
+    assert_eq!(test.line(1), "abc     ijkl");
+}
+
+#[test]
+fn erase_chars_stops_at_line_end() {
+    let mut test = TestTerminal::new();
+    let columns = test.terminal.columns();
+    let line = "x".repeat(columns);
+    test.terminal
+        .process(format!("\x1bc{}\x1b[1;{}H\x1b[99X", line, columns - 2).as_bytes());
+
+    assert_eq!(test.cursor_position(), (0, columns - 3));
+    assert_eq!(test.line(0), "x".repeat(columns - 3));
+    assert_eq!(test.line(1), "");
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use common::TestTerminal;
+
+fn press(test: &mut TestTerminal, scancodes: &[u8]) -> String {
+    scancodes
+        .iter()
+        .filter_map(|&scancode| test.terminal.handle_keyboard(scancode))
+        .collect()
+}
+
+#[test]
+fn printable_keys() {
+    let mut test = TestTerminal::new();
+    assert_eq!(press(&mut test, &[0x1e, 0x9e, 0x30, 0xb0]), "ab");
+    assert_eq!(press(&mut test, &[0x2a, 0x1e, 0x9e, 0xaa]), "A");
+    assert_eq!(press(&mut test, &[0x1c, 0x9c]), "\n");
+}
+
+#[test]
+fn control_keys() {
+    let mut test = TestTerminal::new();
+    assert_eq!(press(&mut test, &[0x1d, 0x2e, 0xae, 0x9d]), "\u{3}");
+    assert_eq!(press(&mut test, &[0xe0, 0x48, 0xe0, 0xc8]), "\x1b[A");
+
+    test.terminal.process(b"\x1b[?1h");
+    assert_eq!(press(&mut test, &[0xe0, 0x48, 0xe0, 0xc8]), "\x1bOA");
+    test.terminal.process(b"\x1b[?1l");
+}
+
+#[test]
+fn shortcuts_produce_no_output() {
+    let mut test = TestTerminal::new();
+    let scroll_up = [0x1d, 0x2a, 0xe0, 0x48, 0xe0, 0xc8, 0xaa, 0x9d];
+    assert_eq!(press(&mut test, &scroll_up), "");
+    assert_eq!(test.take_output(), "");
+}
+
+#[test]
+fn default_shortcuts_ignore_alt() {
+    let mut test = TestTerminal::new();
+    let scroll_up = [0x38, 0x1d, 0x2a, 0xe0, 0x48, 0xe0, 0xc8, 0xaa, 0x9d, 0xb8];
+    assert_eq!(press(&mut test, &scroll_up), "");
+    assert_eq!(test.take_output(), "");
+}
+
+#[test]
+fn injected_chars_go_to_the_pty() {
+    let mut test = TestTerminal::new();
+    test.terminal.inject_char('é');
+    test.terminal.inject_char('中');
+    assert_eq!(test.take_output(), "é中");
+}
+
+#[test]
+fn layout_switch_keeps_terminal_state() {
+    use os_terminal::{layouts, HandleControl, Keyboard, ScancodeSet1};
+
+    let mut test = TestTerminal::new();
+    test.terminal.process(b"\x1b[?1h");
+    assert_eq!(press(&mut test, &[0x15, 0x95]), "y");
+
+    test.terminal
+        .set_keyboard_layout_dynamic(Box::new(Keyboard::new(
+            ScancodeSet1::new(),
+            layouts::De105Key,
+            HandleControl::MapLettersToUnicode,
+        )));
+    assert_eq!(press(&mut test, &[0x15, 0x95]), "z");
+    assert_eq!(press(&mut test, &[0xe0, 0x48, 0xe0, 0xc8]), "\x1bOA");
+    test.terminal.process(b"\x1b[?1l");
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use common::TestTerminal;
+use os_terminal::{MouseButton, MouseInput};
+
+fn click(test: &mut TestTerminal, x: usize, y: usize) -> Option<String> {
+    test.terminal.handle_mouse(MouseInput::Moved(x, y));
+    test.terminal
+        .handle_mouse(MouseInput::Pressed(MouseButton::Left))
+}
+
+#[test]
+fn click_to_move_respects_mouse_reporting() {
+    let mut test = TestTerminal::new();
+    test.terminal.set_click_to_move(true);
+    let (width, height) = (
+        common::WIDTH / test.terminal.columns(),
+        common::HEIGHT / test.terminal.rows(),
+    );
+
+    test.terminal.process(b"\x1bc");
+    assert_eq!(
+        click(&mut test, width * 2, height * 3).as_deref(),
+        Some("\x1b[4;3H")
+    );
+    assert_eq!(test.take_output(), "\x1b[4;3H");
+
+    for mode in [1000, 1002, 1003] {
+        test.terminal.process(format!("\x1b[?{}h", mode).as_bytes());
+        assert_eq!(click(&mut test, width * 2, height * 3), None);
+        test.terminal
+            .process(format!("\x1b[?{}$p", mode).as_bytes());
+        test.terminal.process(format!("\x1b[?{}l", mode).as_bytes());
+    }
+    assert_eq!(
+        test.take_output(),
+        "\x1b[?1000;1$y\x1b[?1002;1$y\x1b[?1003;1$y"
+    );
+    assert!(click(&mut test, 0, 0).is_some());
+
+    test.terminal.set_click_to_move(false);
+}
@@ -0,0 +1,217 @@
+mod common;
+
+use common::TestTerminal;
+
+#[test]
+fn color_scheme_change_respects_manual_flush() {
+    let mut test = TestTerminal::new();
+    test.terminal.set_auto_flush(false);
+    test.terminal.flush();
+    assert_eq!(test.terminal.dirty_rows().count(), 0);
+
+    test.terminal.set_color_scheme(1);
+    assert_eq!(test.terminal.dirty_rows().count(), test.terminal.rows());
+
+    test.terminal.flush();
+    assert_eq!(test.terminal.dirty_rows().count(), 0);
+
+    test.terminal.set_color_scheme(0);
+    test.terminal.set_auto_flush(true);
+    test.terminal.flush();
+}
+
+#[test]
+fn flat_rasters_are_drawn_row_major() {
+    use os_terminal::font::{ContentInfo, FontManager, Rasterized};
+    use os_terminal::{DrawTarget, Rgb, Terminal};
+    use std::sync::{Arc, Mutex};
+
+    static GLYPH: [u8; 6] = [0xff, 0, 0, 0, 0xff, 0xff];
+
+    struct FlatFont;
+
+    impl FontManager for FlatFont {
+        fn size(&self) -> (usize, usize) {
+            (3, 2)
+        }
+
+        fn rasterize(&mut self, _info: ContentInfo) -> Rasterized<'_> {
+            Rasterized::Flat(&GLYPH, 3)
+        }
+    }
+
+    struct Pixels(Arc<Mutex<Vec<Rgb>>>);
+
+    impl DrawTarget for Pixels {
+        fn size(&self) -> (usize, usize) {
+            (3, 2)
+        }
+
+        fn draw_pixel(&mut self, x: usize, y: usize, color: Rgb) {
+            self.0.lock().unwrap()[y * 3 + x] = color;
+        }
+    }
+
+    let _test = TestTerminal::new();
+    let pixels = Arc::new(Mutex::new(vec![(0, 0, 0); 6]));
+    let mut terminal = Terminal::new(Pixels(pixels.clone()));
+    terminal.set_font_manager(Box::new(FlatFont));
+    terminal.process(b"\x1b[?25lx");
+    terminal.flush();
+
+    let pixels = pixels.lock().unwrap();
+    let lit: Vec<bool> = pixels.iter().map(|&pixel| pixel == pixels[0]).collect();
+    assert_eq!(lit, [true, false, false, false, true, true]);
+    assert_ne!(pixels[0], pixels[1]);
+}
+
+#[test]
+fn const_palette_splits_rgb_channels() {
+    use os_terminal::Palette;
+
+    const PALETTE: Palette = Palette::const_new(0xf5f5f5, 0x151515, [0xac4142; 16]);
+
+    assert_eq!(PALETTE.foreground, (0xf5, 0xf5, 0xf5));
+    assert_eq!(PALETTE.background, (0x15, 0x15, 0x15));
+    assert_eq!(PALETTE.ansi_colors, [(0xac, 0x41, 0x42); 16]);
+
+    let mut test = TestTerminal::new();
+    test.terminal.set_custom_color_scheme(PALETTE);
+    test.terminal.set_color_scheme(0);
+}
+
+#[test]
+fn resolve_color_uses_the_given_scheme() {
+    use os_terminal::{resolve_color, Color, ColorScheme, Palette};
+
+    let scheme = ColorScheme::from_palette(&Palette::const_new(0xffffff, 0x000000, [0x111111; 16]));
+
+    assert_eq!(
+        resolve_color(Color::Indexed(256), &scheme),
+        (0xff, 0xff, 0xff)
+    );
+    assert_eq!(resolve_color(Color::Indexed(257), &scheme), (0, 0, 0));
+    assert_eq!(
+        resolve_color(Color::Indexed(1), &scheme),
+        (0x11, 0x11, 0x11)
+    );
+    assert_eq!(resolve_color(Color::Indexed(196), &scheme), (255, 0, 0));
+    assert_eq!(resolve_color(Color::Indexed(232), &scheme), (8, 8, 8));
+    assert_eq!(resolve_color(Color::Rgb((1, 2, 3)), &scheme), (1, 2, 3));
+    assert_eq!(
+        resolve_color(Color::Indexed(258), &scheme),
+        (0xff, 0xff, 0xff)
+    );
+    assert_eq!(
+        resolve_color(Color::Indexed(u16::MAX), &scheme),
+        (0xff, 0xff, 0xff)
+    );
+}
+
+#[test]
+fn scrollbar_width_keeps_screen_state() {
+    let mut test = TestTerminal::new();
+    let columns = test.terminal.columns();
+    test.terminal.process(b"hello\x1b[?1049halt\x1b[?2004h");
+
+    test.terminal.set_scrollbar_width(16);
+    assert!(test.terminal.columns() < columns);
+    assert_eq!(test.line(0), "alt");
+    assert_eq!(test.cursor_position(), (0, 3));
+
+    test.terminal.process(b"\x1b[?2004$p\x1b[?1049l");
+    assert_eq!(test.take_output(), "\x1b[?2004;1$y");
+    assert_eq!(test.line(0), "hello");
+}
+
+#[test]
+fn atlas_bitmap_font_unpacks_bits() {
+    use os_terminal::font::{AtlasBitmapFont, ContentInfo, FontManager, Rasterized};
+
+    static ATLAS: [u8; 4] = [0b1000_0001, 0b0110_0000, 0xff, 0xff];
+    let mut font = AtlasBitmapFont::from_bytes(&ATLAS, 4, 3, '?' as u32, 2);
+    assert_eq!(font.size(), (4, 3));
+
+    let Rasterized::Owned(raster) = font.rasterize(ContentInfo::new('x', false, false, false))
+    else {
+        panic!("expected an owned raster");
+    };
+    assert_eq!(
+        raster,
+        [[0xff, 0, 0, 0], [0, 0, 0, 0xff], [0, 0xff, 0xff, 0]]
+    );
+}
+
+#[test]
+fn color_cache_stats_follow_the_logger() {
+    let mut test = TestTerminal::new();
+    test.terminal.reset_stats();
+    test.terminal.process(b"abc");
+    assert!(test.terminal.render_stats().color_cache.is_none());
+
+    test.terminal.set_logger(Some(|_| {}));
+    test.terminal.process(b"def");
+    let writes = test.terminal.render_stats().color_cache.unwrap().writes;
+    assert!(writes > 0);
+
+    test.terminal.set_logger(None);
+    test.terminal.process(b"ghi");
+    assert_eq!(
+        test.terminal.render_stats().color_cache.unwrap().writes,
+        writes
+    );
+}
+
+#[test]
+fn partial_flush_updates_stats() {
+    let mut test = TestTerminal::new();
+    test.terminal.set_auto_flush(false);
+    test.terminal.process(b"\x1bc");
+    test.terminal.flush();
+    test.terminal.reset_stats();
+
+    test.terminal.process(b"abc");
+    for row in test.terminal.dirty_rows().collect::<Vec<_>>() {
+        test.terminal.draw_row(row);
+    }
+    assert_eq!(test.terminal.render_stats().total_flushes, 0);
+
+    test.terminal.finish_partial_flush();
+    let stats = test.terminal.render_stats();
+    assert_eq!(stats.total_flushes, 1);
+    assert!(stats.total_cells_drawn > 0);
+    test.terminal.set_auto_flush(true);
+}
+
+#[test]
+fn line_attribute_change_marks_row_dirty() {
+    let mut test = TestTerminal::new();
+    test.terminal.set_auto_flush(false);
+    test.terminal.process(b"\x1bc\x1b[3;1Habc");
+    test.terminal.flush();
+    assert_eq!(test.terminal.dirty_rows().count(), 0);
+
+    test.terminal.process(b"\x1b#6");
+    assert_eq!(test.terminal.dirty_rows().collect::<Vec<_>>(), [2]);
+
+    test.terminal.draw_row(2);
+    assert_eq!(test.terminal.dirty_rows().count(), 0);
+    test.terminal.set_auto_flush(true);
+}
+
+#[test]
+fn color_scheme_reflects_changes() {
+    use os_terminal::ColorScheme;
+
+    let mut test = TestTerminal::new();
+    test.terminal.set_color_scheme(1);
+    assert_eq!(test.terminal.color_scheme(), ColorScheme::new(1));
+
+    test.terminal.process(b"\x1b]4;1;rgb:12/34/56\x07");
+    let scheme = test.terminal.color_scheme();
+    assert_ne!(scheme, ColorScheme::new(1));
+    assert_eq!(scheme.ansi_colors[1], (0x12, 0x34, 0x56));
+
+    test.terminal.set_color_scheme(0);
+    assert_eq!(test.terminal.color_scheme(), ColorScheme::default());
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::TestTerminal;
+
+#[test]
+fn report_standard_modes() {
+    let mut test = TestTerminal::new();
+    test.terminal.process(b"\x1bc\x1b[4$p\x1b[20$p\x1b[3$p");
+    assert_eq!(test.take_output(), "\x1b[4;2$y\x1b[20;2$y\x1b[3;0$y");
+
+    test.terminal.process(b"\x1b[4h\x1b[20h\x1b[4$p\x1b[20$p");
+    assert_eq!(test.take_output(), "\x1b[4;1$y\x1b[20;1$y");
+
+    test.terminal.process(b"\x1b[4l\x1b[4$p");
+    assert_eq!(test.take_output(), "\x1b[4;2$y");
+}
+
+#[test]
+fn report_private_modes() {
+    let mut test = TestTerminal::new();
+    test.terminal
+        .process(b"\x1bc\x1b[?25$p\x1b[?2004$p\x1b[?2004h\x1b[?2004$p");
+    assert_eq!(
+        test.take_output(),
+        "\x1b[?25;1$y\x1b[?2004;2$y\x1b[?2004;1$y"
+    );
+}
+
+#[test]
+fn title_push_and_pop() {
+    use std::sync::Mutex;
+
+    static TITLES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    let mut test = TestTerminal::new();
+    test.terminal
+        .set_title_handler(Some(|title| TITLES.lock().unwrap().push(title.into())));
+
+    test.terminal
+        .process(b"\x1b]2;first\x07\x1b[22t\x1b]0;second\x07");
+    assert_eq!(test.terminal.title(), "second");
+
+    test.terminal.process(b"\x1b[23t");
+    assert_eq!(test.terminal.title(), "first");
+    assert_eq!(*TITLES.lock().unwrap(), ["first", "second", "first"]);
+
+    test.terminal.process(b"\x1b[23t");
+    assert_eq!(test.terminal.title(), "first");
+
+    for index in 0..20 {
+        test.terminal
+            .process(format!("\x1b]2;{}\x07\x1b[22t", index).as_bytes());
+    }
+    for _ in 0..20 {
+        test.terminal.process(b"\x1b[23t");
+    }
+    assert_eq!(test.terminal.title(), "4");
+
+    test.terminal.set_title_handler(None);
+}
+
+#[test]
+fn secondary_device_attributes() {
+    let mut test = TestTerminal::new();
+    test.terminal.set_secondary_da(1, "2.3.4", 0);
+    test.terminal.process(b"\x1b[>c");
+    assert_eq!(test.take_output(), "\x1b[>1;20304;0c");
+
+    test.terminal
+        .set_secondary_da(0, env!("CARGO_PKG_VERSION"), 1);
+}
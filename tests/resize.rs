@@ -0,0 +1,62 @@
+use std::sync::{Arc, Mutex};
+
+use os_terminal::font::BitmapFont;
+use os_terminal::{DrawTarget, Rgb, Terminal};
+
+struct ResizableDisplay(Arc<Mutex<(usize, usize)>>);
+
+impl DrawTarget for ResizableDisplay {
+    fn size(&self) -> (usize, usize) {
+        *self.0.lock().unwrap()
+    }
+
+    fn draw_pixel(&mut self, x: usize, y: usize, _color: Rgb) {
+        let (width, height) = self.size();
+        assert!(
+            x < width && y < height,
+            "pixel ({}, {}) out of bounds",
+            x,
+            y
+        );
+    }
+}
+
+fn line(terminal: &Terminal<ResizableDisplay>, row: usize) -> String {
+    let content = terminal.accessible_text();
+    let line = content.lines[row]
+        .spans
+        .iter()
+        .map(|span| span.text.as_str());
+    line.collect::<String>().trim_end().to_string()
+}
+
+#[test]
+fn resize_keeps_screen_contents() {
+    let size = Arc::new(Mutex::new((800, 600)));
+    let mut terminal = Terminal::new(ResizableDisplay(size.clone()));
+    terminal.set_font_manager(Box::new(BitmapFont));
+    let (rows, columns) = (terminal.rows(), terminal.columns());
+
+    terminal.process(b"hello\r\nworld\x1b[11;1Hbottom\x1b[?1049h");
+    *size.lock().unwrap() = (400, 300);
+    terminal.resize();
+    assert!(terminal.rows() < rows && terminal.columns() < columns);
+    assert_eq!(line(&terminal, 0), "");
+
+    terminal.process(b"\x1b[?1049l");
+    assert_eq!(line(&terminal, 0), "hello");
+    assert_eq!(line(&terminal, 1), "world");
+
+    let font_height = 600 / rows;
+    *size.lock().unwrap() = (800, font_height * 5);
+    terminal.resize();
+    assert_eq!(terminal.rows(), 5);
+    assert_eq!(line(&terminal, 4), "bottom");
+
+    *size.lock().unwrap() = (800, 600);
+    terminal.resize();
+    assert_eq!((terminal.rows(), terminal.columns()), (rows, columns));
+    assert_eq!(line(&terminal, 4), "bottom");
+    terminal.process(b"!");
+    assert_eq!(line(&terminal, 4), "bottom!");
+}
@@ -0,0 +1,236 @@
+mod common;
+
+use common::TestTerminal;
+
+const CASES: usize = 300;
+
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn range(&mut self, start: usize, end: usize) -> usize {
+        start + self.next() as usize % (end - start + 1)
+    }
+}
+
+fn row_text(row: usize) -> String {
+    format!("row{}", row)
+}
+
+fn fill_rows(test: &mut TestTerminal) {
+    let rows = test.terminal.rows();
+    test.terminal.process(b"\x1bc");
+    for row in 0..rows {
+        let text = format!("\x1b[{};1H{}", row + 1, row_text(row));
+        test.terminal.process(text.as_bytes());
+    }
+}
+
+fn expected_line(row: usize, (top, bottom): (usize, usize), count: usize, is_up: bool) -> String {
+    if row < top || row > bottom {
+        return row_text(row);
+    }
+    let source = if is_up {
+        row.checked_add(count).filter(|&source| source <= bottom)
+    } else {
+        row.checked_sub(count).filter(|&source| source >= top)
+    };
+    source.map(row_text).unwrap_or_default()
+}
+
+fn check_scroll(test: &mut TestTerminal, region: (usize, usize), count: usize, is_up: bool) {
+    let (top, bottom) = region;
+    let rows = test.terminal.rows();
+    fill_rows(test);
+
+    let command = if is_up { 'S' } else { 'T' };
+    let sequence = format!("\x1b[{};{}r\x1b[{}{}", top + 1, bottom + 1, count, command);
+    test.terminal.process(sequence.as_bytes());
+
+    let effective = if top < bottom { region } else { (0, rows - 1) };
+    for row in 0..rows {
+        assert_eq!(
+            test.line(row),
+            expected_line(row, effective, count, is_up),
+            "row {} after {:?} scroll {} by {}",
+            row,
+            region,
+            if is_up { "up" } else { "down" },
+            count
+        );
+    }
+
+    test.terminal.flush();
+    assert_eq!(test.terminal.dirty_rows().count(), 0);
+}
+
+#[test]
+fn scroll_region_random() {
+    let mut test = TestTerminal::new();
+    test.terminal.set_auto_flush(false);
+    let rows = test.terminal.rows();
+    let mut rng = XorShift(0x9e37_79b9_7f4a_7c15);
+
+    for _ in 0..CASES {
+        let top = rng.range(0, rows - 1);
+        let bottom = rng.range(top, rows - 1);
+        let count = rng.range(1, rows + 2);
+        let is_up = rng.next() & 1 == 0;
+        check_scroll(&mut test, (top, bottom), count, is_up);
+    }
+    test.terminal.set_auto_flush(true);
+}
+
+#[test]
+fn scroll_region_edges() {
+    let mut test = TestTerminal::new();
+    test.terminal.set_auto_flush(false);
+    let rows = test.terminal.rows();
+    let last = rows - 1;
+
+    let regions = [
+        (0, last),
+        (0, 1),
+        (last - 1, last),
+        (0, 0),
+        (last, last),
+        (3, 3),
+    ];
+    for region in regions {
+        for count in [1, 2, rows - 1, rows, rows + 1] {
+            for is_up in [true, false] {
+                check_scroll(&mut test, region, count, is_up);
+            }
+        }
+    }
+    test.terminal.set_auto_flush(true);
+}
+
+#[test]
+fn linefeed_at_bottom_of_default_region_scrolls() {
+    let mut test = TestTerminal::new();
+    fill_rows(&mut test);
+    let rows = test.terminal.rows();
+
+    test.terminal
+        .process(format!("\x1b[{};1H\nx", rows).as_bytes());
+    assert_eq!(test.line(0), row_text(1));
+    assert_eq!(test.line(rows - 2), row_text(rows - 1));
+    assert_eq!(test.line(rows - 1), "x");
+}
+
+#[test]
+fn scroll_region_without_bottom_extends_to_last_row() {
+    let mut test = TestTerminal::new();
+    fill_rows(&mut test);
+    let rows = test.terminal.rows();
+
+    test.terminal
+        .process(format!("\x1b[3r\x1b[{};1H\nx", rows).as_bytes());
+    assert_eq!(test.line(0), row_text(0));
+    assert_eq!(test.line(1), row_text(1));
+    assert_eq!(test.line(2), row_text(3));
+    assert_eq!(test.line(rows - 2), row_text(rows - 1));
+    assert_eq!(test.line(rows - 1), "x");
+}
+
+#[test]
+fn linefeed_below_scroll_region_does_not_scroll() {
+    let mut test = TestTerminal::new();
+    fill_rows(&mut test);
+    let rows = test.terminal.rows();
+
+    let sequence = format!("\x1b[1;{}r\x1b[{};1H\n\nx", rows - 1, rows);
+    test.terminal.process(sequence.as_bytes());
+    for row in 0..rows - 1 {
+        assert_eq!(test.line(row), row_text(row));
+    }
+    assert_eq!(
+        test.line(rows - 1),
+        format!("x{}", &row_text(rows - 1)[1..])
+    );
+
+    let sequence = format!("\x1b[{};1H\ny", rows - 1);
+    test.terminal.process(sequence.as_bytes());
+    assert_eq!(test.line(0), row_text(1));
+    assert_eq!(test.line(rows - 2), "y");
+    assert_eq!(
+        test.line(rows - 1),
+        format!("x{}", &row_text(rows - 1)[1..])
+    );
+}
+
+#[test]
+fn alt_screen_restores_scroll_region() {
+    let mut test = TestTerminal::new();
+    fill_rows(&mut test);
+    let rows = test.terminal.rows();
+
+    assert!(!test.terminal.is_alt_screen_active());
+    let sequence = format!("\x1b[?1049h\x1b[1;{}r", rows - 1);
+    test.terminal.process(sequence.as_bytes());
+    assert!(test.terminal.is_alt_screen_active());
+    test.terminal.process(b"\x1b[?1049l");
+    assert!(!test.terminal.is_alt_screen_active());
+    test.terminal
+        .process(format!("\x1b[{};1H\nx", rows).as_bytes());
+    assert_eq!(test.line(0), row_text(1));
+    assert_eq!(test.line(rows - 1), "x");
+}
+
+static SCROLL_EVENTS: std::sync::Mutex<Vec<(usize, usize)>> = std::sync::Mutex::new(Vec::new());
+
+fn record_scroll(position: usize, total: usize) {
+    SCROLL_EVENTS.lock().unwrap().push((position, total));
+}
+
+#[test]
+fn scroll_handler_follows_history_view() {
+    let mut test = TestTerminal::new();
+    let rows = test.terminal.rows();
+    test.terminal.process(b"\x1bc");
+    for row in 0..rows + 10 {
+        test.terminal
+            .process(format!("{}\r\n", row_text(row)).as_bytes());
+    }
+
+    SCROLL_EVENTS.lock().unwrap().clear();
+    test.terminal.set_scroll_handler(Some(record_scroll));
+
+    test.terminal
+        .handle_mouse(os_terminal::MouseInput::Scroll(3));
+    test.terminal
+        .handle_mouse(os_terminal::MouseInput::Scroll(-1));
+    test.terminal
+        .handle_mouse(os_terminal::MouseInput::Scroll(-5));
+    test.terminal
+        .handle_mouse(os_terminal::MouseInput::Scroll(-1));
+    test.terminal.set_scroll_handler(None);
+
+    assert_eq!(*SCROLL_EVENTS.lock().unwrap(), [(3, 11), (2, 11), (0, 11)]);
+}
+
+#[test]
+fn scroll_history_by_reports_lines_scrolled() {
+    let mut test = TestTerminal::new();
+    let rows = test.terminal.rows();
+    test.terminal.process(b"\x1bc");
+    for row in 0..rows + 10 {
+        test.terminal
+            .process(format!("{}\r\n", row_text(row)).as_bytes());
+    }
+
+    assert_eq!(test.terminal.scroll_history_by(4), 4);
+    assert_eq!(test.line(0), row_text(7));
+    assert_eq!(test.terminal.scroll_history_by(100), 7);
+    assert_eq!(test.line(0), row_text(0));
+    assert_eq!(test.terminal.scroll_history_by(-3), -3);
+    assert_eq!(test.terminal.scroll_history_by(-100), -8);
+    assert_eq!(test.terminal.scroll_history_by(-1), 0);
+}
@@ -0,0 +1,30 @@
+#![cfg(feature = "truetype")]
+
+use os_terminal::font::{ContentInfo, FontManager, Rasterized, TrueTypeFont};
+
+fn raster(font: &mut TrueTypeFont, content: char) -> Vec<Vec<u8>> {
+    match font.rasterize(ContentInfo::new(content, false, false, false)) {
+        Rasterized::Vec(raster) => raster.clone(),
+        _ => panic!("expected an outline glyph"),
+    }
+}
+
+#[test]
+fn memory_limited_cache_still_rasterizes() {
+    let font_buffer = include_bytes!("../examples/FiraCodeNotoSans.ttf");
+    let mut reference = TrueTypeFont::new(10.0, font_buffer);
+    let (width, height) = reference.size();
+    let mut font = TrueTypeFont::new(10.0, font_buffer).with_cache_memory_limit(width * height);
+
+    for content in ['a', 'b', 'a', 'c', 'b'] {
+        assert_eq!(raster(&mut font, content), raster(&mut reference, content));
+    }
+}
+
+#[test]
+fn tiny_font_size_keeps_one_pixel() {
+    let font_buffer = include_bytes!("../examples/FiraCodeNotoSans.ttf");
+    let mut font = TrueTypeFont::new(0.1, font_buffer);
+    assert_eq!(font.size(), (1, 1));
+    assert_eq!(raster(&mut font, 'a').len(), 1);
+}
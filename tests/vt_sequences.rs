@@ -0,0 +1,105 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::TestTerminal;
+
+enum Section {
+    Input(Vec<u8>),
+    ExpectText(Vec<String>),
+    ExpectCursor(usize, usize),
+}
+
+fn unescape(line: &str) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buffer = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Some('e') => bytes.push(0x1b),
+            Some('n') => bytes.push(b'\n'),
+            Some('r') => bytes.push(b'\r'),
+            Some('t') => bytes.push(b'\t'),
+            Some('\\') => bytes.push(b'\\'),
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                bytes.push(u8::from_str_radix(&hex, 16).expect("invalid \\x escape"));
+            }
+            other => panic!("invalid escape: \\{:?}", other),
+        }
+    }
+
+    bytes
+}
+
+fn parse_fixture(content: &str) -> Vec<Section> {
+    let mut sections = Vec::new();
+
+    for line in content.lines() {
+        if line.starts_with('#') {
+            continue;
+        }
+        if line == "INPUT:" {
+            sections.push(Section::Input(Vec::new()));
+        } else if line == "EXPECT_TEXT:" {
+            sections.push(Section::ExpectText(Vec::new()));
+        } else if let Some(position) = line.strip_prefix("EXPECT_CURSOR:") {
+            let mut numbers = position.split_whitespace().map(|n| n.parse().unwrap());
+            let (row, column) = (numbers.next().unwrap(), numbers.next().unwrap());
+            sections.push(Section::ExpectCursor(row, column));
+        } else {
+            match sections.last_mut() {
+                Some(Section::Input(input)) => input.extend(unescape(line)),
+                Some(Section::ExpectText(text)) => text.push(line.trim_end().to_string()),
+                _ if line.trim().is_empty() => {}
+                _ => panic!("unexpected line outside of a section: {:?}", line),
+            }
+        }
+    }
+
+    sections
+}
+
+fn run_fixture(path: &Path) {
+    let name = path.file_name().unwrap().to_string_lossy();
+    let content = fs::read_to_string(path).unwrap();
+    let mut test = TestTerminal::new();
+    test.terminal.process(b"\x1bc");
+
+    for section in parse_fixture(&content) {
+        match section {
+            Section::Input(input) => test.terminal.process(&input),
+            Section::ExpectText(expected) => {
+                for (row, line) in expected.iter().enumerate() {
+                    assert_eq!(&test.line(row), line, "{}: row {}", name, row);
+                }
+            }
+            Section::ExpectCursor(row, column) => {
+                let position = test.cursor_position();
+                assert_eq!(position, (row, column), "{}: cursor", name);
+            }
+        }
+    }
+}
+
+#[test]
+fn vt_fixtures() {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let mut fixtures: Vec<_> = fs::read_dir(directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "vt"))
+        .collect();
+    fixtures.sort();
+
+    assert!(!fixtures.is_empty());
+    for fixture in fixtures {
+        run_fixture(&fixture);
+    }
+}
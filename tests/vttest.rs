@@ -0,0 +1,222 @@
+mod common;
+
+use common::TestTerminal;
+
+fn setup() -> (TestTerminal, usize, usize) {
+    let mut test = TestTerminal::new();
+    test.terminal.process(b"\x1bc");
+    let (rows, columns) = (test.terminal.rows(), test.terminal.columns());
+    (test, rows, columns)
+}
+
+fn assert_screen(test: &TestTerminal, expected: &[String]) {
+    let mut screen = test.screen();
+    screen.truncate(expected.len());
+    assert_eq!(screen, expected);
+}
+
+fn lines(lines: &[&str]) -> Vec<String> {
+    lines.iter().map(|line| line.to_string()).collect()
+}
+
+#[test]
+fn cursor_positioning_frame() {
+    let (mut test, rows, columns) = setup();
+
+    let mut input = String::from("\x1b[2J");
+    for row in 1..=rows {
+        input += &format!("\x1b[{};1H*\x1b[{};{}H*", row, row, columns);
+    }
+    for column in 2..columns {
+        input += &format!("\x1b[1;{}H*\x1b[{};{}H*", column, rows, column);
+    }
+    test.terminal.process(input.as_bytes());
+
+    let border = "*".repeat(columns);
+    let inner = format!("*{}*", " ".repeat(columns - 2));
+    let mut expected = vec![border.clone()];
+    expected.extend((2..rows).map(|_| inner.clone()));
+    expected.push(border);
+    assert_screen(&test, &expected);
+}
+
+#[test]
+fn cursor_movement_is_clamped() {
+    let (mut test, rows, columns) = setup();
+
+    test.terminal.process(b"\x1b[H\x1b[5B\x1b[3C");
+    assert_eq!(test.cursor_position(), (5, 3));
+    test.terminal.process(b"\x1b[99A");
+    assert_eq!(test.cursor_position(), (0, 3));
+    test.terminal.process(b"\x1b[99D");
+    assert_eq!(test.cursor_position(), (0, 0));
+    test.terminal.process(b"\x1b[999B");
+    assert_eq!(test.cursor_position(), (rows - 1, 0));
+    test.terminal.process(b"\x1b[999C");
+    assert_eq!(test.cursor_position(), (rows - 1, columns - 1));
+    test.terminal.process(b"\x1b[3;7f\x1b[2d\x1b[4G");
+    assert_eq!(test.cursor_position(), (1, 3));
+}
+
+#[test]
+fn erase_operations() {
+    let (mut test, _, _) = setup();
+    let fill = b"\x1b[Habcdefgh\r\nabcdefgh\r\nabcdefgh\r\nabcdefgh";
+
+    test.terminal.process(fill);
+    test.terminal.process(b"\x1b[2;4H\x1b[J");
+    assert_screen(&test, &lines(&["abcdefgh", "abc", "", ""]));
+
+    test.terminal.process(fill);
+    test.terminal.process(b"\x1b[3;4H\x1b[1J");
+    assert_screen(&test, &lines(&["", "", "    efgh", "abcdefgh"]));
+
+    test.terminal.process(fill);
+    test.terminal.process(b"\x1b[2J");
+    assert_screen(&test, &lines(&["", "", "", ""]));
+
+    test.terminal.process(fill);
+    test.terminal
+        .process(b"\x1b[1;3H\x1b[3X\x1b[2;6H\x1b[K\x1b[3;3H\x1b[1K\x1b[4;1H\x1b[2K");
+    assert_screen(&test, &lines(&["ab   fgh", "abcde", "   defgh", ""]));
+}
+
+#[test]
+fn insert_and_delete() {
+    let (mut test, _, _) = setup();
+
+    test.terminal.process(b"\x1b[Habcdefgh\x1b[1;3H\x1b[3@XY");
+    assert_screen(&test, &lines(&["abXY cdefgh"]));
+
+    test.terminal.process(b"\x1b[1;1H\x1b[4P");
+    assert_screen(&test, &lines(&[" cdefgh"]));
+
+    test.terminal
+        .process(b"\x1b[2J\x1b[H1\r\n2\r\n3\r\n4\x1b[2;1H\x1b[2L");
+    assert_screen(&test, &lines(&["1", "", "", "2", "3", "4"]));
+
+    test.terminal.process(b"\x1b[1;1H\x1b[3M");
+    assert_screen(&test, &lines(&["2", "3", "4", ""]));
+}
+
+#[test]
+fn scroll_region() {
+    let (mut test, _, _) = setup();
+
+    test.terminal.process(b"\x1b[H1\r\n2\r\n3\r\n4\r\n5\r\n6");
+    test.terminal.process(b"\x1b[2;4r");
+    assert_eq!(test.cursor_position(), (0, 0));
+
+    test.terminal.process(b"\x1b[4;1H\n\n");
+    assert_screen(&test, &lines(&["1", "4", "", "", "5", "6"]));
+
+    test.terminal.process(b"\x1b[2;1H\x1bM\x1bMx");
+    assert_screen(&test, &lines(&["1", "x", "", "4", "5", "6"]));
+
+    test.terminal.process(b"\x1b[r\x1b[6;1H");
+    assert_eq!(test.cursor_position(), (5, 0));
+}
+
+#[test]
+fn reverse_index_and_index() {
+    let (mut test, rows, _) = setup();
+
+    test.terminal.process(b"\x1b[H1\r\n2\r\n3\x1b[H\x1bMx");
+    assert_screen(&test, &lines(&["x", "1", "2", "3"]));
+
+    test.terminal
+        .process(format!("\x1b[{};1H\x1bDy", rows).as_bytes());
+    assert_eq!(test.line(0), "1");
+    assert_eq!(test.line(rows - 1), "y");
+
+    test.terminal.process(b"\x1b[1;3H\x1bEz");
+    assert_eq!(test.line(1), "z");
+    assert_eq!(test.cursor_position(), (1, 1));
+}
+
+#[test]
+fn index_and_line_feed_modes() {
+    let (mut test, _, _) = setup();
+
+    test.terminal.process(b"ab\x1bDc\x1bEd");
+    assert_screen(&test, &lines(&["ab", "  c", "d"]));
+    assert_eq!(test.cursor_position(), (2, 1));
+
+    test.terminal.set_auto_crnl(false);
+    test.terminal.process(b"\x1bc\x1b[Hab\ncd");
+    assert_screen(&test, &lines(&["ab", "  cd"]));
+
+    test.terminal.process(b"\x1bc\x1b[20hab\ncd\x1bDe\x1bEf");
+    assert_screen(&test, &lines(&["ab", "cd", "  e", "f"]));
+    assert_eq!(test.cursor_position(), (3, 1));
+
+    test.terminal.process(b"\x1b[20l");
+    test.terminal.set_auto_crnl(true);
+}
+
+#[test]
+fn character_sets() {
+    let (mut test, _, _) = setup();
+
+    test.terminal
+        .process(b"\x1b(0lqwqk\r\nx x x\r\ntqnqu\r\nmqvqj\x1b(B");
+    assert_screen(&test, &lines(&["┌─┬─┐", "│ │ │", "├─┼─┤", "└─┴─┘"]));
+
+    test.terminal
+        .process(b"\x1b[2J\x1b[H\x1b)0a\x0ea\x0fa\x1bNa");
+    assert_screen(&test, &lines(&["a▒aa"]));
+}
+
+#[test]
+fn screen_alignment() {
+    let (mut test, rows, columns) = setup();
+
+    test.terminal.process(b"abc\x1b#8");
+    let expected = vec!["E".repeat(columns); rows];
+    assert_screen(&test, &expected);
+    assert_eq!(test.cursor_position(), (0, 0));
+}
+
+#[test]
+fn screen_alignment_ignores_default_cell() {
+    use os_terminal::{Cell, Flags};
+
+    let (mut test, _, columns) = setup();
+    let mut hidden = Cell::default();
+    hidden.flags.insert(Flags::HIDDEN);
+    test.terminal.set_default_cell(hidden);
+
+    test.terminal.process(b"\x1b#8");
+    assert_eq!(test.line(1), "E".repeat(columns));
+
+    test.terminal.set_default_cell(Cell::default());
+}
+
+#[test]
+fn iter_rows_matches_screen() {
+    let (mut test, rows, columns) = setup();
+    test.terminal.process("ab\r\n中c".as_bytes());
+
+    let cells: Vec<_> = test.terminal.iter_rows().collect();
+    assert_eq!(cells.len(), rows);
+    assert!(cells.iter().all(|row| row.len() == columns));
+
+    let text: String = cells[1]
+        .iter()
+        .filter(|cell| !cell.placeholder)
+        .map(|cell| cell.content)
+        .collect();
+    assert_eq!(text.trim_end(), "中c");
+    assert_eq!(cells[0][1].content, 'b');
+}
+
+#[test]
+fn cell_display_shows_content_and_flags() {
+    let (mut test, _, _) = setup();
+    test.terminal
+        .process("\x1b[1;4mA\x1b[0m 中\x1b[3;9mx".as_bytes());
+
+    let row: Vec<_> = test.terminal.iter_rows().next().unwrap().to_vec();
+    let shown: Vec<_> = row[..5].iter().map(|cell| cell.to_string()).collect();
+    assert_eq!(shown, ["A:BU", ".", "中", ".", "x:IS"]);
+}
@@ -0,0 +1,108 @@
+use std::sync::Mutex;
+
+use os_terminal::font::{BitmapFont, ContentInfo, FontManager, Rasterized};
+use os_terminal::{DrawTarget, HardwareCursor, MouseButton, MouseInput, Rgb, Terminal};
+
+static SERIAL: Mutex<()> = Mutex::new(());
+
+struct SmallDisplay(usize, usize);
+
+impl DrawTarget for SmallDisplay {
+    fn size(&self) -> (usize, usize) {
+        (self.0, self.1)
+    }
+
+    fn draw_pixel(&mut self, x: usize, y: usize, _color: Rgb) {
+        assert!(
+            x < self.0 && y < self.1,
+            "pixel ({}, {}) out of bounds",
+            x,
+            y
+        );
+    }
+}
+
+struct NullCursor;
+
+impl HardwareCursor for NullCursor {
+    fn set_cursor_position(&mut self, _x: usize, _y: usize) {}
+    fn set_cursor_visible(&mut self, _visible: bool) {}
+}
+
+struct EmptyFont;
+
+impl FontManager for EmptyFont {
+    fn size(&self) -> (usize, usize) {
+        (0, 0)
+    }
+
+    fn rasterize(&mut self, _info: ContentInfo) -> Rasterized<'_> {
+        Rasterized::Owned(Vec::new())
+    }
+}
+
+#[test]
+fn displays_smaller_than_a_cell() {
+    let _serial = SERIAL.lock().unwrap_or_else(|error| error.into_inner());
+    for size in [(0, 0), (4, 4), (800, 0), (0, 600)] {
+        let mut terminal = Terminal::new(SmallDisplay(size.0, size.1));
+        terminal.set_font_manager(Box::new(BitmapFont));
+        assert_eq!(terminal.rows() * terminal.columns(), 0);
+
+        terminal.process(b"hello\r\n\x1b[2J\x1b[5;5H\x1b[3L\x1b[2Pworld\t\x1b[r");
+        terminal.set_color_scheme(1);
+        terminal.set_color_scheme(0);
+        terminal.flush();
+    }
+}
+
+#[test]
+fn zero_size_font() {
+    let _serial = SERIAL.lock().unwrap_or_else(|error| error.into_inner());
+    let mut terminal = Terminal::new(SmallDisplay(800, 600));
+    terminal.set_font_manager(Box::new(EmptyFont));
+    assert_eq!(terminal.rows() * terminal.columns(), 0);
+
+    terminal.process(b"hello\r\n\x1b[5;5Hworld");
+    terminal.flush();
+
+    terminal.set_font_manager(Box::new(BitmapFont));
+    assert!(terminal.rows() > 0 && terminal.columns() > 0);
+    terminal.process(b"hello");
+}
+
+#[test]
+fn one_column_display() {
+    let _serial = SERIAL.lock().unwrap_or_else(|error| error.into_inner());
+    let mut terminal = Terminal::new(SmallDisplay(12, 600));
+    terminal.set_font_manager(Box::new(BitmapFont));
+    assert_eq!(terminal.columns(), 1);
+
+    terminal.process("a中b\t\x1b#6c中\x1b[2@\x1b[2P".as_bytes());
+    terminal.flush();
+}
+
+#[test]
+fn empty_grid_input() {
+    let _serial = SERIAL.lock().unwrap_or_else(|error| error.into_inner());
+    let mut terminal = Terminal::new(SmallDisplay(0, 0));
+    terminal.set_font_manager(Box::new(BitmapFont));
+    terminal.set_click_to_move(true);
+
+    terminal.handle_ime_preedit("ab", None);
+    terminal.handle_ime_preedit("", None);
+    terminal.handle_mouse(MouseInput::Moved(3, 3));
+    assert_eq!(
+        terminal.handle_mouse(MouseInput::Pressed(MouseButton::Left)),
+        None
+    );
+    terminal.handle_mouse(MouseInput::Scroll(-3));
+    terminal.scroll_history_by(3);
+
+    terminal.set_scrollbar_width(4);
+    terminal.flush();
+    terminal.set_hardware_cursor(Some(Box::new(NullCursor)));
+    terminal.process(b"hello");
+    terminal.flush();
+    terminal.set_hardware_cursor(None);
+}